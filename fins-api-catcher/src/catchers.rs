@@ -0,0 +1,293 @@
+use crate::{guard_error, ApiError, Negotiated, Problem};
+use rocket::catcher::{self, Catcher};
+use rocket::fairing::{self, Fairing, Info, Kind};
+use rocket::http::uri::Origin;
+use rocket::http::{Header, Status};
+use rocket::response::Responder;
+use rocket::{Build, Request, Rocket};
+use std::borrow::Cow;
+
+/// Customizes the response of [`ApiCatchers`] for one status.
+///
+/// ```
+/// use fins_api_catcher::CatcherOptions;
+///
+/// let options = CatcherOptions::new()
+///     .message("Too many requests, try again in a minute.")
+///     .retry_after(60);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct CatcherOptions {
+    message: Option<Cow<'static, str>>,
+    reason: Option<Cow<'static, str>>,
+    headers: Vec<Header<'static>>,
+}
+
+impl CatcherOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Replaces the reason phrase as message, a recorded [`crate::GuardFailure`] still takes precedence.
+    pub fn message(mut self, message: impl Into<Cow<'static, str>>) -> Self {
+        self.message = Some(message.into());
+        self
+    }
+
+    /// Replaces the machine-readable reason, see [`ApiError::with_reason`].
+    pub fn reason(mut self, reason: impl Into<Cow<'static, str>>) -> Self {
+        self.reason = Some(reason.into());
+        self
+    }
+
+    /// Adds a header to the response, headers with the same name are all kept.
+    pub fn header(mut self, header: impl Into<Header<'static>>) -> Self {
+        self.headers.push(header.into());
+        self
+    }
+
+    /// Adds a `Retry-After` header with the delay in seconds.
+    pub fn retry_after(self, seconds: u64) -> Self {
+        self.header(Header::new("Retry-After", seconds.to_string()))
+    }
+
+    /// Adds a `WWW-Authenticate` header, like `Bearer realm="api"`.
+    pub fn www_authenticate(self, challenge: impl Into<Cow<'static, str>>) -> Self {
+        self.header(Header::new("WWW-Authenticate", challenge))
+    }
+}
+
+/// Registers the API catchers for a base path, with [`CatcherOptions`] for specific statuses.
+///
+/// ```
+/// use fins_api_catcher::{ApiCatchers, CatcherOptions};
+/// use rocket::http::Status;
+///
+/// let rocket = rocket::build()
+///     .attach(
+///         ApiCatchers::new()
+///             .on(Status::TooManyRequests, CatcherOptions::new().retry_after(60))
+///             .on(
+///                 Status::Unauthorized,
+///                 CatcherOptions::new().www_authenticate("Bearer"),
+///             ),
+///     )
+///     .attach(ApiCatchers::new().base("/v2").problem_details());
+/// ```
+#[derive(Debug, Clone)]
+pub struct ApiCatchers {
+    base: Cow<'static, str>,
+    problem_details: bool,
+    statuses: Vec<(Status, CatcherOptions)>,
+}
+
+impl Default for ApiCatchers {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ApiCatchers {
+    /// Catchers for `/` that respond like [`crate::api_catcher`].
+    pub fn new() -> Self {
+        Self {
+            base: "/".into(),
+            problem_details: false,
+            statuses: Vec::new(),
+        }
+    }
+
+    /// Registers the catchers for `base` instead of `/`.
+    pub fn base(mut self, base: impl Into<Cow<'static, str>>) -> Self {
+        self.base = base.into();
+        self
+    }
+
+    /// Always responds with `application/problem+json` like [`crate::problem_catcher`].
+    pub fn problem_details(mut self) -> Self {
+        self.problem_details = true;
+        self
+    }
+
+    /// Uses `options` for `status`, replacing earlier options for it.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `status` is not an error status (4xx or 5xx).
+    pub fn on(mut self, status: Status, options: CatcherOptions) -> Self {
+        assert!(
+            (400..600).contains(&status.code),
+            "`{status}` is not an error status"
+        );
+        self.statuses.retain(|(s, _)| *s != status);
+        self.statuses.push((status, options));
+        self
+    }
+
+    fn catchers(&self) -> Vec<Catcher> {
+        let handler = |options| ApiCatcher {
+            problem_details: self.problem_details,
+            options,
+        };
+        let mut catchers = vec![Catcher::new(None, handler(CatcherOptions::default()))];
+        for (status, options) in &self.statuses {
+            catchers.push(Catcher::new(status.code, handler(options.clone())));
+        }
+        catchers
+    }
+}
+
+#[rocket::async_trait]
+impl Fairing for ApiCatchers {
+    fn info(&self) -> Info {
+        Info {
+            name: "API Catchers",
+            kind: Kind::Ignite,
+        }
+    }
+
+    async fn on_ignite(&self, rocket: Rocket<Build>) -> fairing::Result {
+        if let Err(e) = Origin::parse(&self.base) {
+            log::error!("invalid API catchers base `{}`: {e}", self.base);
+            return Err(rocket);
+        }
+        Ok(rocket.register(self.base.as_ref(), self.catchers()))
+    }
+}
+
+#[derive(Clone)]
+struct ApiCatcher {
+    problem_details: bool,
+    options: CatcherOptions,
+}
+
+#[rocket::async_trait]
+impl catcher::Handler for ApiCatcher {
+    async fn handle<'r>(&self, status: Status, req: &'r Request<'_>) -> catcher::Result<'r> {
+        let options = &self.options;
+        let mut error = guard_error(status, req).unwrap_or_else(|| match &options.message {
+            Some(message) => ApiError::new(status, message.clone()),
+            None => status.into(),
+        });
+        if let Some(reason) = &options.reason {
+            error = error.with_reason(reason.clone());
+        }
+        let mut response = if self.problem_details {
+            Problem::from(error).respond_to(req)?
+        } else {
+            Negotiated(error).respond_to(req)?
+        };
+        for header in &options.headers {
+            response.adjoin_header(header.clone());
+        }
+        Ok(response)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{ApiCatchers, CatcherOptions};
+    use rocket::error::ErrorKind;
+    use rocket::http::{ContentType, Status};
+    use rocket::local::blocking::Client;
+    use rocket::serde::json::{json, Value};
+    use rocket::{get, routes};
+
+    #[get("/limited")]
+    fn limited() -> Status {
+        Status::TooManyRequests
+    }
+
+    #[get("/private")]
+    fn private() -> Status {
+        Status::Unauthorized
+    }
+
+    fn client() -> Client {
+        let rocket = rocket::build()
+            .mount("/", routes![limited, private])
+            .mount("/v2", routes![limited])
+            .attach(
+                ApiCatchers::new()
+                    .on(
+                        Status::TooManyRequests,
+                        CatcherOptions::new()
+                            .message("slow down")
+                            .reason("RATE_LIMITED")
+                            .retry_after(60),
+                    )
+                    .on(
+                        Status::Unauthorized,
+                        CatcherOptions::new()
+                            .www_authenticate("Bearer")
+                            .www_authenticate("Basic"),
+                    ),
+            )
+            .attach(ApiCatchers::new().base("/v2").problem_details().on(
+                Status::TooManyRequests,
+                CatcherOptions::new().retry_after(1),
+            ));
+        Client::tracked(rocket).unwrap()
+    }
+
+    #[test]
+    fn statuses() {
+        let client = client();
+        let response = client.get("/limited").dispatch();
+        assert_eq!(response.status(), Status::TooManyRequests);
+        assert_eq!(response.headers().get_one("Retry-After"), Some("60"));
+        assert_eq!(
+            response.into_json::<Value>().unwrap(),
+            json!({ "error": { "code": 429, "message": "slow down", "reason": "RATE_LIMITED" } })
+        );
+
+        let response = client.get("/private").dispatch();
+        assert_eq!(response.status(), Status::Unauthorized);
+        let challenges: Vec<_> = response.headers().get("WWW-Authenticate").collect();
+        assert_eq!(challenges, ["Bearer", "Basic"]);
+        assert_eq!(
+            response.into_json::<Value>().unwrap(),
+            json!({ "error": { "code": 401, "message": "Unauthorized", "reason": "UNAUTHORIZED" } })
+        );
+
+        // statuses without options use the default catcher
+        let response = client.get("/missing").dispatch();
+        assert_eq!(response.status(), Status::NotFound);
+        assert_eq!(response.headers().get_one("Retry-After"), None);
+        assert_eq!(response.content_type(), Some(ContentType::JSON));
+    }
+
+    #[test]
+    fn base() {
+        let client = client();
+        let response = client.get("/v2/limited").dispatch();
+        assert_eq!(response.status(), Status::TooManyRequests);
+        assert_eq!(response.headers().get_one("Retry-After"), Some("1"));
+        assert_eq!(
+            response.content_type(),
+            Some(ContentType::new("application", "problem+json"))
+        );
+        let body = response.into_json::<Value>().unwrap();
+        assert_eq!(body["title"], "Too Many Requests");
+
+        let response = client.get("/v2/missing").dispatch();
+        assert_eq!(response.status(), Status::NotFound);
+        assert_eq!(
+            response.content_type(),
+            Some(ContentType::new("application", "problem+json"))
+        );
+    }
+
+    #[test]
+    fn invalid_base() {
+        let rocket = rocket::build().attach(ApiCatchers::new().base("no slash?"));
+        let error = Client::tracked(rocket).err().unwrap();
+        assert!(matches!(error.kind(), ErrorKind::FailedFairings(_)));
+    }
+
+    #[test]
+    #[should_panic(expected = "`200 OK` is not an error status")]
+    fn success_status() {
+        let _ = ApiCatchers::new().on(Status::Ok, CatcherOptions::new());
+    }
+}
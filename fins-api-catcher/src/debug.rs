@@ -0,0 +1,339 @@
+use crate::GuardFailure;
+use rocket::fairing::{self, Fairing, Info, Kind};
+use rocket::http::Status;
+use rocket::serde::json::Value;
+use rocket::{Build, Config, Data, Request, Response, Rocket};
+use serde_json::Map;
+use std::backtrace::Backtrace;
+use std::cell::RefCell;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::Once;
+use tokio::task;
+
+const DEBUG_ERRORS_KEY: &str = "debug_errors";
+
+/// Adds a `debug` object with the route, the matched URI, the guard failure and panics to error bodies.
+///
+/// Attaching [`DebugDetails::fairing`] enables it for the `debug` profile, the `debug_errors`
+/// config value (like `ROCKET_DEBUG_ERRORS=true`) overrides that. Builds without debug assertions
+/// never include the details, whatever the configuration says.
+pub struct DebugDetails;
+
+/// Managed if the details are enabled.
+struct Enabled;
+
+impl DebugDetails {
+    pub fn fairing() -> impl Fairing {
+        DebugDetailsFairing
+    }
+}
+
+struct DebugDetailsFairing;
+
+#[rocket::async_trait]
+impl Fairing for DebugDetailsFairing {
+    fn info(&self) -> Info {
+        Info {
+            name: "Debug Error Details",
+            kind: Kind::Ignite | Kind::Request | Kind::Response,
+        }
+    }
+
+    async fn on_ignite(&self, rocket: Rocket<Build>) -> fairing::Result {
+        let figment = rocket.figment();
+        let enabled = match figment.extract_inner::<bool>(DEBUG_ERRORS_KEY) {
+            Ok(enabled) => enabled,
+            Err(_) => figment.profile() == Config::DEBUG_PROFILE,
+        };
+        if !enabled {
+            return Ok(rocket);
+        }
+        if !cfg!(debug_assertions) {
+            log::warn!("ignoring `{DEBUG_ERRORS_KEY}`, debug error details are only available in debug builds");
+            return Ok(rocket);
+        }
+        install_panic_hook();
+        Ok(rocket.manage(Enabled))
+    }
+
+    async fn on_request(&self, req: &mut Request<'_>, _data: &mut Data<'_>) {
+        if req.rocket().state::<Enabled>().is_none() {
+            return;
+        }
+        IN_FLIGHT.fetch_add(1, Ordering::SeqCst);
+        LAST_PANIC.with(|panic| panic.borrow_mut().take());
+        let scope = PanicScope {
+            task: task::try_id(),
+            start: PANICS.load(Ordering::SeqCst),
+        };
+        req.local_cache(|| Some(scope));
+    }
+
+    async fn on_response<'r>(&self, req: &'r Request<'_>, _res: &mut Response<'r>) {
+        if req.local_cache(|| None::<PanicScope>).is_some() {
+            IN_FLIGHT.fetch_sub(1, Ordering::SeqCst);
+        }
+    }
+}
+
+/// Requests of Rocket instances with enabled details that are being handled.
+static IN_FLIGHT: AtomicUsize = AtomicUsize::new(0);
+
+/// Number of panics captured so far, to tell apart panics from before a request started.
+static PANICS: AtomicU64 = AtomicU64::new(0);
+
+/// Which panics belong to a request.
+#[derive(Clone, Copy)]
+struct PanicScope {
+    /// The task handling the request, `None` outside of tasks like in `block_on`.
+    task: Option<task::Id>,
+    /// The value of [`PANICS`] when the request started.
+    start: u64,
+}
+
+struct CapturedPanic {
+    number: u64,
+    task: Option<task::Id>,
+    message: String,
+    backtrace: String,
+}
+
+thread_local! {
+    /// The last panic on this thread.
+    ///
+    /// Rocket catches handler panics and calls the catcher in the same poll, so the catcher
+    /// runs on the thread and in the task the handler panicked in. Panics of other tasks, like
+    /// other requests or tasks spawned by the handler, are told apart by the task, and panics
+    /// of earlier requests by their number, see [`PanicScope`].
+    static LAST_PANIC: RefCell<Option<CapturedPanic>> = const { RefCell::new(None) };
+}
+
+/// Installs a panic hook that captures the message and backtrace while requests are being handled.
+///
+/// The hook calls the previous one and stays installed for the life of the process, also after
+/// the Rocket instance is dropped, but it does nothing while no request is in flight.
+fn install_panic_hook() {
+    static INSTALL: Once = Once::new();
+    INSTALL.call_once(|| {
+        let previous = std::panic::take_hook();
+        std::panic::set_hook(Box::new(move |info| {
+            if IN_FLIGHT.load(Ordering::SeqCst) > 0 {
+                let panic = CapturedPanic {
+                    number: PANICS.fetch_add(1, Ordering::SeqCst) + 1,
+                    task: task::try_id(),
+                    message: info.to_string(),
+                    backtrace: Backtrace::force_capture().to_string(),
+                };
+                LAST_PANIC.with(|last| *last.borrow_mut() = Some(panic));
+            }
+            previous(info);
+        }));
+    });
+}
+
+/// The panic captured on this thread in the task of `req` since it started, if any.
+fn take_panic(req: &Request<'_>) -> Option<CapturedPanic> {
+    let scope = (*req.local_cache(|| None::<PanicScope>))?;
+    LAST_PANIC
+        .with(|last| last.borrow_mut().take())
+        .filter(|panic| panic.task == scope.task && panic.number > scope.start)
+}
+
+/// The `debug` object for errors of `req`, if the details are enabled.
+pub(crate) fn details(status: Status, req: &Request<'_>) -> Option<Value> {
+    if !cfg!(debug_assertions) {
+        return None;
+    }
+    req.rocket().state::<Enabled>()?;
+    let mut details = Map::new();
+    if let Some(route) = req.route() {
+        if let Some(name) = &route.name {
+            details.insert("route".into(), name.to_string().into());
+        }
+        details.insert("route_uri".into(), route.uri.to_string().into());
+    }
+    details.insert("uri".into(), req.uri().to_string().into());
+    if let Some(failure) = GuardFailure::get(req) {
+        details.insert("guard_failure".into(), failure.message().into());
+    }
+    if status == Status::InternalServerError {
+        if let Some(panic) = take_panic(req) {
+            details.insert("panic".into(), panic.message.into());
+            details.insert("backtrace".into(), panic.backtrace.into());
+        }
+    }
+    Some(Value::Object(details))
+}
+
+// the details are never included without debug assertions
+#[cfg(all(test, debug_assertions))]
+mod tests {
+    use crate::{api_catcher, problem_catcher, ApiError, DebugDetails, Recorded};
+    use fins_streamelements_channel::Channel;
+    use rocket::figment::Figment;
+    use rocket::http::Status;
+    use rocket::local::asynchronous;
+    use rocket::local::blocking::Client;
+    use rocket::serde::json::{json, Value};
+    use rocket::{catch, catchers, get, routes, Config};
+    use std::sync::Arc;
+
+    #[get("/channel/<_id>")]
+    fn channel(_id: u32, channel: Recorded<Channel<'_>>) -> String {
+        channel.to_string()
+    }
+
+    #[get("/panic")]
+    fn panic() -> &'static str {
+        panic!("boom")
+    }
+
+    #[get("/fail")]
+    fn fail() -> ApiError {
+        ApiError::new(Status::InternalServerError, "db down")
+    }
+
+    #[get("/spawned")]
+    async fn spawned() -> ApiError {
+        let background = tokio::spawn(async { panic!("background") });
+        assert!(background.await.is_err());
+        ApiError::new(Status::InternalServerError, "db down")
+    }
+
+    #[get("/slow")]
+    async fn slow() -> ApiError {
+        // let the other requests run until they are done
+        for _ in 0..100 {
+            tokio::task::yield_now().await;
+        }
+        ApiError::new(Status::InternalServerError, "db down")
+    }
+
+    #[catch(500)]
+    fn plain() -> &'static str {
+        "plain"
+    }
+
+    fn client_with(figment: Figment) -> Client {
+        let rocket = rocket::custom(figment)
+            .attach(DebugDetails::fairing())
+            .mount("/", routes![channel, panic, fail, spawned, slow])
+            .mount("/plain", routes![panic])
+            .register("/", catchers![api_catcher])
+            .register("/plain", catchers![plain])
+            .register("/v2", catchers![problem_catcher]);
+        Client::tracked(rocket).unwrap()
+    }
+
+    #[test]
+    fn debug_profile() {
+        let client = client_with(Config::figment().select(Config::DEBUG_PROFILE));
+        let response = client.get("/channel/1?page=2").dispatch();
+        assert_eq!(response.status(), Status::BadRequest);
+        let body = response.into_json::<Value>().unwrap();
+        assert_eq!(
+            body["error"]["debug"],
+            json!({
+                "route": "channel",
+                "route_uri": "/channel/<_id>",
+                "uri": "/channel/1?page=2",
+                "guard_failure": "expected header `x-streamelements-channel`"
+            })
+        );
+
+        let response = client.get("/panic").dispatch();
+        assert_eq!(response.status(), Status::InternalServerError);
+        let body = response.into_json::<Value>().unwrap();
+        let debug = &body["error"]["debug"];
+        assert_eq!(debug["route"], "panic");
+        let panic = debug["panic"].as_str().unwrap();
+        assert!(panic.contains("boom"), "{panic}");
+        assert!(debug["backtrace"].is_string());
+
+        let response = client.get("/missing").dispatch();
+        let body = response.into_json::<Value>().unwrap();
+        assert_eq!(body["error"]["debug"], json!({ "uri": "/missing" }));
+        let response = client.get("/v2/missing").dispatch();
+        let body = response.into_json::<Value>().unwrap();
+        assert_eq!(body["debug"], json!({ "uri": "/v2/missing" }));
+    }
+
+    #[test]
+    fn panic_of_other_request() {
+        let client = client_with(Config::figment().select(Config::DEBUG_PROFILE));
+        // the panic is not consumed by the plain catcher
+        let response = client.get("/plain/panic").dispatch();
+        assert_eq!(response.status(), Status::InternalServerError);
+        assert_eq!(response.into_string().unwrap(), "plain");
+
+        let response = client.get("/fail").dispatch();
+        assert_eq!(response.status(), Status::InternalServerError);
+        let body = response.into_json::<Value>().unwrap();
+        let debug = &body["error"]["debug"];
+        assert_eq!(debug["route"], "fail");
+        assert_eq!(debug.get("panic"), None);
+        assert_eq!(debug.get("backtrace"), None);
+    }
+
+    #[test]
+    fn panic_of_other_task() {
+        // all tasks run on the same thread, like the requests handled by one worker thread
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap();
+        runtime.block_on(async {
+            let figment = Config::figment().select(Config::DEBUG_PROFILE);
+            let rocket = rocket::custom(figment)
+                .attach(DebugDetails::fairing())
+                .mount("/", routes![spawned, slow])
+                .mount("/plain", routes![panic])
+                .register("/", catchers![api_catcher])
+                .register("/plain", catchers![plain]);
+            let client = Arc::new(asynchronous::Client::tracked(rocket).await.unwrap());
+            // dispatched in tasks like the requests of a server
+            let dispatch = |uri: &'static str| {
+                let client = client.clone();
+                tokio::spawn(async move {
+                    let response = client.get(uri).dispatch().await;
+                    (response.status(), response.into_string().await.unwrap())
+                })
+            };
+
+            let (status, body) = dispatch("/spawned").await.unwrap();
+            assert_eq!(status, Status::InternalServerError);
+            let body: Value = rocket::serde::json::from_str(&body).unwrap();
+            assert_eq!(body["error"]["debug"]["route"], "spawned");
+            assert_eq!(body["error"]["debug"].get("panic"), None);
+
+            let slow = dispatch("/slow");
+            let plain = dispatch("/plain/panic");
+            assert_eq!(plain.await.unwrap().1, "plain");
+            let (status, body) = slow.await.unwrap();
+            assert_eq!(status, Status::InternalServerError);
+            let body: Value = rocket::serde::json::from_str(&body).unwrap();
+            assert_eq!(body["error"]["debug"]["route"], "slow");
+            assert_eq!(body["error"]["debug"].get("panic"), None);
+        });
+    }
+
+    #[test]
+    fn config() {
+        let figment = Config::figment().select(Config::DEBUG_PROFILE);
+        let client = client_with(figment.clone().merge(("debug_errors", false)));
+        let response = client.get("/missing").dispatch();
+        let body = response.into_json::<Value>().unwrap();
+        assert_eq!(body["error"].get("debug"), None);
+
+        let client = client_with(Config::figment().select("staging"));
+        let response = client.get("/missing").dispatch();
+        let body = response.into_json::<Value>().unwrap();
+        assert_eq!(body["error"].get("debug"), None);
+
+        let figment = Config::figment().select("staging");
+        let client = client_with(figment.merge(("debug_errors", true)));
+        let response = client.get("/missing").dispatch();
+        let body = response.into_json::<Value>().unwrap();
+        assert_eq!(body["error"]["debug"], json!({ "uri": "/missing" }));
+    }
+}
@@ -0,0 +1,281 @@
+use rocket::data::{self, Data, FromData};
+use rocket::http::Status;
+use rocket::outcome::Outcome;
+use rocket::request::{self, FromRequest};
+use rocket::Request;
+use std::ops::Deref;
+use std::sync::Mutex;
+
+/// Why a request or data guard failed, recorded with [`RecordFailure`] and used by the catchers.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GuardFailure {
+    status: Status,
+    message: String,
+    /// Position of JSON parse errors.
+    location: Option<(usize, usize)>,
+}
+
+/// The request-local slot of the last [`GuardFailure`].
+#[derive(Default)]
+struct Slot(Mutex<Option<GuardFailure>>);
+
+impl GuardFailure {
+    /// Uses the `Display` message of `error` and the line and column of a `serde_json` error in its sources.
+    pub fn new<E: std::error::Error + ?Sized>(status: Status, error: &E) -> Self {
+        let mut location = None;
+        let mut source = error.source();
+        while let Some(error) = source {
+            if let Some(error) = error.downcast_ref::<serde_json::Error>() {
+                // line 0 means the error has no position, like for I/O errors
+                if error.line() > 0 {
+                    location = Some((error.line(), error.column()));
+                }
+                break;
+            }
+            source = error.source();
+        }
+        Self {
+            status,
+            message: error.to_string(),
+            location,
+        }
+    }
+
+    /// For guards whose error does not implement `std::error::Error`.
+    pub fn from_message(status: Status, message: impl Into<String>) -> Self {
+        Self {
+            status,
+            message: message.into(),
+            location: None,
+        }
+    }
+
+    pub fn status(&self) -> Status {
+        self.status
+    }
+
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    pub fn line(&self) -> Option<usize> {
+        self.location.map(|(line, _)| line)
+    }
+
+    pub fn column(&self) -> Option<usize> {
+        self.location.map(|(_, column)| column)
+    }
+
+    /// Stores the failure for the request, replacing an earlier one.
+    pub fn record(self, req: &Request<'_>) {
+        let slot = req.local_cache(Slot::default);
+        *slot.0.lock().unwrap_or_else(|e| e.into_inner()) = Some(self);
+    }
+
+    /// The failure recorded for the request, if any.
+    pub fn get(req: &Request<'_>) -> Option<Self> {
+        let slot = req.local_cache(Slot::default);
+        let failure = slot.0.lock().unwrap_or_else(|e| e.into_inner());
+        failure.clone()
+    }
+}
+
+/// Records the error of a failed guard outcome as [`GuardFailure`].
+///
+/// ```
+/// use fins_api_catcher::RecordFailure;
+/// use rocket::http::Status;
+/// use rocket::request::{self, FromRequest, Outcome, Request};
+///
+/// struct Page(u32);
+///
+/// #[rocket::async_trait]
+/// impl<'r> FromRequest<'r> for Page {
+///     type Error = std::num::ParseIntError;
+///
+///     async fn from_request(req: &'r Request<'_>) -> request::Outcome<Self, Self::Error> {
+///         let page = req.headers().get_one("x-page").unwrap_or("1");
+///         match page.parse() {
+///             Ok(page) => Outcome::Success(Page(page)),
+///             Err(e) => Outcome::Error((Status::BadRequest, e)),
+///         }
+///         .record_failure(req)
+///     }
+/// }
+/// ```
+pub trait RecordFailure {
+    fn record_failure(self, req: &Request<'_>) -> Self;
+}
+
+impl<S, E, F> RecordFailure for Outcome<S, (Status, E), F>
+where
+    E: std::error::Error,
+{
+    fn record_failure(self, req: &Request<'_>) -> Self {
+        if let Outcome::Error((status, error)) = &self {
+            GuardFailure::new(*status, error).record(req);
+        }
+        self
+    }
+}
+
+/// Wraps a request or data guard and records its failure for the catchers.
+///
+/// Use it like `Recorded<Channel<'_>>` or `Recorded<Json<T>>` instead of the guard itself.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Recorded<T>(pub T);
+
+impl<T> Recorded<T> {
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T> Deref for Recorded<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+#[rocket::async_trait]
+impl<'r, T> FromRequest<'r> for Recorded<T>
+where
+    T: FromRequest<'r>,
+    T::Error: std::error::Error,
+{
+    type Error = T::Error;
+
+    async fn from_request(req: &'r Request<'_>) -> request::Outcome<Self, Self::Error> {
+        T::from_request(req).await.record_failure(req).map(Recorded)
+    }
+}
+
+#[rocket::async_trait]
+impl<'r, T> FromData<'r> for Recorded<T>
+where
+    T: FromData<'r>,
+    T::Error: std::error::Error,
+{
+    type Error = T::Error;
+
+    async fn from_data(req: &'r Request<'_>, data: Data<'r>) -> data::Outcome<'r, Self> {
+        T::from_data(req, data)
+            .await
+            .record_failure(req)
+            .map(Recorded)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{api_catcher, problem_catcher, GuardFailure, Recorded};
+    use fins_streamelements_channel::Channel;
+    use rocket::http::{ContentType, Status};
+    use rocket::local::blocking::Client;
+    use rocket::serde::json::{json, Json, Value};
+    use rocket::{catchers, get, post, routes};
+    use serde::Deserialize;
+
+    #[derive(Deserialize)]
+    struct Message {
+        #[allow(dead_code)]
+        text: String,
+    }
+
+    #[get("/channel")]
+    fn channel(channel: Recorded<Channel<'_>>) -> String {
+        channel.to_string()
+    }
+
+    #[post("/message", data = "<message>")]
+    fn message(message: Recorded<Json<Message>>) -> &'static str {
+        let _ = message.into_inner();
+        "ok"
+    }
+
+    fn client() -> Client {
+        let rocket = rocket::build()
+            .mount("/", routes![channel, message])
+            .mount("/v2", routes![channel, message])
+            .register("/", catchers![api_catcher])
+            .register("/v2", catchers![problem_catcher]);
+        Client::tracked(rocket).unwrap()
+    }
+
+    #[test]
+    fn request_guard() {
+        let client = client();
+        let response = client.get("/channel").dispatch();
+        assert_eq!(response.status(), Status::BadRequest);
+        assert_eq!(
+            response.into_json::<Value>().unwrap(),
+            json!({
+                "error": {
+                    "code": 400,
+                    "message": "expected header `x-streamelements-channel`",
+                    "reason": "BAD_REQUEST"
+                }
+            })
+        );
+
+        let response = client.get("/v2/channel").dispatch();
+        assert_eq!(
+            response.into_json::<Value>().unwrap(),
+            json!({
+                "type": "about:blank",
+                "title": "Bad Request",
+                "status": 400,
+                "detail": "expected header `x-streamelements-channel`",
+                "instance": "/v2/channel",
+                "reason": "BAD_REQUEST"
+            })
+        );
+    }
+
+    #[test]
+    fn data_guard() {
+        let client = client();
+        let response = client
+            .post("/message")
+            .header(ContentType::JSON)
+            .body("{\n  \"txt\": 1\n}")
+            .dispatch();
+        assert_eq!(response.status(), Status::UnprocessableEntity);
+        assert_eq!(
+            response.into_json::<Value>().unwrap(),
+            json!({
+                "error": {
+                    "code": 422,
+                    "message": "parse error: missing field `text` at line 3 column 1",
+                    "reason": "UNPROCESSABLE_ENTITY",
+                    "details": { "line": 3, "column": 1 }
+                }
+            })
+        );
+
+        let response = client
+            .post("/message")
+            .header(ContentType::JSON)
+            .body(r#"{"text": "hi""#)
+            .dispatch();
+        assert_eq!(response.status(), Status::BadRequest);
+        let body = response.into_json::<Value>().unwrap();
+        assert_eq!(body["error"]["details"], json!({ "line": 1, "column": 13 }));
+
+        let response = client
+            .post("/message")
+            .header(ContentType::JSON)
+            .body(r#"{"text": "hi"}"#)
+            .dispatch();
+        assert_eq!(response.status(), Status::Ok);
+    }
+
+    #[test]
+    fn from_message() {
+        let failure = GuardFailure::from_message(Status::Unauthorized, "invalid token");
+        assert_eq!(failure.message(), "invalid token");
+        assert_eq!(failure.line(), None);
+    }
+}
@@ -1,13 +1,39 @@
 use rocket::catch;
 use rocket::http::Status;
-use rocket::serde::json::Json;
+use rocket::response::{self, Responder};
+use rocket::serde::json::{json, Json, Value};
 use rocket::Request;
 use serde::Serialize;
+use std::borrow::Cow;
+
+mod catchers;
+mod debug;
+mod guard;
+mod negotiate;
+mod problem;
+mod request_id;
+
+pub use catchers::{ApiCatchers, CatcherOptions};
+pub use debug::DebugDetails;
+pub use guard::{GuardFailure, RecordFailure, Recorded};
+pub use negotiate::{escape_html, ErrorPage, Negotiated};
+pub use problem::{problem_catcher, Problem};
+pub use request_id::RequestId;
 
 #[derive(Debug, Serialize)]
 struct JsonError {
     code: u16,
-    message: &'static str,
+    message: Cow<'static, str>,
+    /// Machine-readable, like `NOT_FOUND` or `QUOTA_EXCEEDED`.
+    reason: Cow<'static, str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    details: Option<Value>,
+    /// Set when responding if the [`RequestId`] fairing is attached.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    request_id: Option<String>,
+    /// Set when responding if [`DebugDetails`] are enabled.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    debug: Option<Value>,
 }
 
 #[derive(Debug, Serialize)]
@@ -15,21 +41,186 @@ pub struct ErrorResponse {
     error: JsonError,
 }
 
-#[catch(default)]
-pub fn api_catcher(status: Status, _req: &Request) -> Json<ErrorResponse> {
-    Json(ErrorResponse {
-        error: JsonError {
-            code: status.code,
-            message: status.reason().unwrap_or("Something went wrong"),
-        },
+/// An error response with the same `{"error": {...}}` body as [`api_catcher`].
+///
+/// ```
+/// use fins_api_catcher::ApiError;
+/// use rocket::http::Status;
+/// use rocket::serde::json::json;
+///
+/// let error = ApiError::new(Status::TooManyRequests, "slow down")
+///     .with_reason("RATE_LIMITED")
+///     .with_details(json!({ "limit": 10 }));
+/// ```
+#[derive(Debug)]
+pub struct ApiError {
+    status: Status,
+    // boxed to keep `Result<T, ApiError>` small
+    response: Box<ErrorResponse>,
+}
+
+impl ApiError {
+    /// An error with a custom message, the reason is derived from the status like `NOT_FOUND`.
+    pub fn new(status: Status, message: impl Into<Cow<'static, str>>) -> Self {
+        Self {
+            status,
+            response: Box::new(ErrorResponse {
+                error: JsonError {
+                    code: status.code,
+                    message: message.into(),
+                    reason: default_reason(status),
+                    details: None,
+                    request_id: None,
+                    debug: None,
+                },
+            }),
+        }
+    }
+
+    /// Replaces the machine-readable reason.
+    pub fn with_reason(mut self, reason: impl Into<Cow<'static, str>>) -> Self {
+        self.response.error.reason = reason.into();
+        self
+    }
+
+    /// Adds a `details` value to the body, usually an object.
+    pub fn with_details(mut self, details: Value) -> Self {
+        self.response.error.details = Some(details);
+        self
+    }
+
+    pub fn status(&self) -> Status {
+        self.status
+    }
+
+    pub fn message(&self) -> &str {
+        &self.response.error.message
+    }
+
+    pub fn reason(&self) -> &str {
+        &self.response.error.reason
+    }
+}
+
+impl From<Status> for ApiError {
+    /// An error with the reason phrase of the status as message.
+    fn from(status: Status) -> Self {
+        ApiError::new(status, status.reason().unwrap_or("Something went wrong"))
+    }
+}
+
+impl<'r> Responder<'r, 'static> for ApiError {
+    fn respond_to(mut self, req: &'r Request<'_>) -> response::Result<'static> {
+        self.response.error.request_id = RequestId::get(req).map(|id| id.to_string());
+        self.response.error.debug = debug::details(self.status, req);
+        (self.status, Json(self.response)).respond_to(req)
+    }
+}
+
+/// `Not Found` becomes `NOT_FOUND`, `I'm a teapot` becomes `IM_A_TEAPOT`.
+fn default_reason(status: Status) -> Cow<'static, str> {
+    match status.reason() {
+        Some(reason) => reason
+            .chars()
+            .filter(|c| *c != '\'')
+            .map(|c| match c {
+                ' ' | '-' => '_',
+                c => c.to_ascii_uppercase(),
+            })
+            .collect::<String>()
+            .into(),
+        None => "UNKNOWN".into(),
+    }
+}
+
+/// An error with the message of the [`GuardFailure`] recorded for `status`, if any.
+pub(crate) fn guard_error(status: Status, req: &Request<'_>) -> Option<ApiError> {
+    let failure = GuardFailure::get(req).filter(|failure| failure.status() == status)?;
+    let error = ApiError::new(status, failure.message().to_owned());
+    Some(match (failure.line(), failure.column()) {
+        (Some(line), Some(column)) => error.with_details(json!({ "line": line, "column": column })),
+        _ => error,
     })
 }
 
+/// Responds with an [`ApiError`] for the status, see [`Negotiated`] for the formats.
+///
+/// Failures recorded by guards with [`RecordFailure`] or [`Recorded`] replace the message.
+#[catch(default)]
+pub fn api_catcher(status: Status, req: &Request) -> Negotiated {
+    guard_error(status, req)
+        .unwrap_or_else(|| status.into())
+        .into()
+}
+
 #[cfg(test)]
 mod tests {
+    use crate::{api_catcher, ApiError};
+    use rocket::http::{ContentType, Status};
+    use rocket::local::blocking::Client;
+    use rocket::serde::json::{json, Value};
+    use rocket::{catchers, get, routes};
+
     #[test]
     fn it_works() {
         let result = 2 + 2;
         assert_eq!(result, 4);
     }
+
+    #[get("/limited")]
+    fn limited() -> Result<&'static str, ApiError> {
+        Err(ApiError::new(Status::TooManyRequests, "slow down")
+            .with_reason("RATE_LIMITED")
+            .with_details(json!({ "limit": 10 })))
+    }
+
+    #[get("/teapot")]
+    fn teapot() -> ApiError {
+        Status::ImATeapot.into()
+    }
+
+    fn client() -> Client {
+        let rocket = rocket::build()
+            .mount("/", routes![limited, teapot])
+            .register("/", catchers![api_catcher]);
+        Client::tracked(rocket).unwrap()
+    }
+
+    #[test]
+    fn catcher() {
+        let client = client();
+        let response = client.get("/missing").dispatch();
+        assert_eq!(response.status(), Status::NotFound);
+        assert_eq!(response.content_type(), Some(ContentType::JSON));
+        assert_eq!(
+            response.into_json::<Value>().unwrap(),
+            json!({ "error": { "code": 404, "message": "Not Found", "reason": "NOT_FOUND" } })
+        );
+    }
+
+    #[test]
+    fn api_error() {
+        let client = client();
+        let response = client.get("/limited").dispatch();
+        assert_eq!(response.status(), Status::TooManyRequests);
+        assert_eq!(response.content_type(), Some(ContentType::JSON));
+        assert_eq!(
+            response.into_json::<Value>().unwrap(),
+            json!({
+                "error": {
+                    "code": 429,
+                    "message": "slow down",
+                    "reason": "RATE_LIMITED",
+                    "details": { "limit": 10 }
+                }
+            })
+        );
+
+        let response = client.get("/teapot").dispatch();
+        assert_eq!(response.status(), Status::ImATeapot);
+        assert_eq!(
+            response.into_json::<Value>().unwrap(),
+            json!({ "error": { "code": 418, "message": "I'm a teapot", "reason": "IM_A_TEAPOT" } })
+        );
+    }
 }
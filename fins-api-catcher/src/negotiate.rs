@@ -0,0 +1,264 @@
+use crate::{ApiError, Problem};
+use rocket::http::{ContentType, Header, MediaType};
+use rocket::response::{self, Responder};
+use rocket::Request;
+use std::fmt::Write;
+
+/// The formats an [`ApiError`] can be rendered in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Format {
+    Json,
+    Problem,
+    Html,
+    Text,
+}
+
+impl Format {
+    fn from_media_type(media_type: &MediaType) -> Option<Self> {
+        let (top, sub) = (media_type.top(), media_type.sub());
+        if top == "*" && sub == "*" {
+            Some(Format::Json)
+        } else if top == "application" {
+            if sub == "json" || sub == "*" {
+                Some(Format::Json)
+            } else if sub == "problem+json" {
+                Some(Format::Problem)
+            } else {
+                None
+            }
+        } else if top == "text" {
+            if sub == "html" {
+                Some(Format::Html)
+            } else if sub == "plain" || sub == "*" {
+                Some(Format::Text)
+            } else {
+                None
+            }
+        } else {
+            None
+        }
+    }
+
+    /// The supported format with the highest weight in the `Accept` header, JSON if there is none.
+    fn from_request(req: &Request<'_>) -> Self {
+        let Some(accept) = req.accept() else {
+            return Format::Json;
+        };
+        let mut best: Option<(Format, f32)> = None;
+        for media_type in accept.iter() {
+            let weight = media_type.weight_or(1.0);
+            if weight <= 0.0 {
+                continue;
+            }
+            if let Some(format) = Format::from_media_type(media_type) {
+                // on ties the first one wins
+                if best.is_none_or(|(_, best)| weight > best) {
+                    best = Some((format, weight));
+                }
+            }
+        }
+        best.map_or(Format::Json, |(format, _)| format)
+    }
+}
+
+/// Renders the HTML page of [`Negotiated`] responses.
+///
+/// Manage it to replace the default page, like `rocket.manage(ErrorPage::new(|error| ...))`.
+/// The returned string is sent as is, so messages have to be escaped, see [`escape_html`].
+pub struct ErrorPage(Box<dyn Fn(&ApiError) -> String + Send + Sync>);
+
+impl ErrorPage {
+    pub fn new<F>(render: F) -> Self
+    where
+        F: Fn(&ApiError) -> String + Send + Sync + 'static,
+    {
+        ErrorPage(Box::new(render))
+    }
+
+    pub fn render(&self, error: &ApiError) -> String {
+        (self.0)(error)
+    }
+}
+
+impl Default for ErrorPage {
+    fn default() -> Self {
+        ErrorPage::new(|error| {
+            let status = escape_html(&error.status().to_string());
+            format!(
+                "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{status}</title>\n</head>\n<body>\n<h1>{status}</h1>\n<p>{}</p>\n</body>\n</html>\n",
+                escape_html(error.message())
+            )
+        })
+    }
+}
+
+/// Escapes `&`, `<`, `>`, `"` and `'`.
+pub fn escape_html(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Responds with an [`ApiError`] as JSON, problem+json, HTML or plain text, depending on the `Accept` header.
+///
+/// Without an `Accept` header, or if none of the formats is accepted, JSON is used.
+#[derive(Debug)]
+pub struct Negotiated(pub ApiError);
+
+impl From<ApiError> for Negotiated {
+    fn from(error: ApiError) -> Self {
+        Negotiated(error)
+    }
+}
+
+impl<'r> Responder<'r, 'static> for Negotiated {
+    fn respond_to(self, req: &'r Request<'_>) -> response::Result<'static> {
+        let error = self.0;
+        let status = error.status();
+        let mut response = match Format::from_request(req) {
+            Format::Json => error.respond_to(req)?,
+            Format::Problem => Problem::from(error).respond_to(req)?,
+            Format::Html => {
+                let page = match req.rocket().state::<ErrorPage>() {
+                    Some(page) => page.render(&error),
+                    None => ErrorPage::default().render(&error),
+                };
+                (status, (ContentType::HTML, page)).respond_to(req)?
+            }
+            Format::Text => {
+                let mut text = status.to_string();
+                if Some(error.message()) != status.reason() {
+                    let _ = write!(text, ": {}", error.message());
+                }
+                (status, (ContentType::Plain, text)).respond_to(req)?
+            }
+        };
+        response.adjoin_header(Header::new("Vary", "Accept"));
+        Ok(response)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{api_catcher, ApiError, ErrorPage};
+    use rocket::http::{Accept, ContentType, Header, MediaType, Status};
+    use rocket::local::blocking::Client;
+    use rocket::serde::json::{json, Value};
+    use rocket::{catchers, get, routes};
+
+    #[get("/limited")]
+    fn limited() -> Result<&'static str, crate::Negotiated> {
+        Err(ApiError::new(Status::TooManyRequests, "<slow down>"))?
+    }
+
+    fn client(rocket: rocket::Rocket<rocket::Build>) -> Client {
+        let rocket = rocket
+            .mount("/", routes![limited])
+            .register("/", catchers![api_catcher]);
+        Client::tracked(rocket).unwrap()
+    }
+
+    fn content_type(client: &Client, accept: &'static str) -> Option<ContentType> {
+        let response = client
+            .get("/missing")
+            .header(Header::new("Accept", accept))
+            .dispatch();
+        assert_eq!(response.status(), Status::NotFound);
+        response.content_type()
+    }
+
+    #[test]
+    fn formats() {
+        let client = client(rocket::build());
+
+        let response = client.get("/missing").dispatch();
+        assert_eq!(response.content_type(), Some(ContentType::JSON));
+        assert_eq!(response.headers().get_one("Vary"), Some("Accept"));
+        assert_eq!(
+            response.into_json::<Value>().unwrap(),
+            json!({ "error": { "code": 404, "message": "Not Found", "reason": "NOT_FOUND" } })
+        );
+
+        let problem = Some(ContentType::new("application", "problem+json"));
+        assert_eq!(
+            content_type(&client, "application/json"),
+            Some(ContentType::JSON)
+        );
+        assert_eq!(content_type(&client, "application/problem+json"), problem);
+        assert_eq!(
+            content_type(&client, "text/plain"),
+            Some(ContentType::Plain)
+        );
+        assert_eq!(content_type(&client, "*/*"), Some(ContentType::JSON));
+        assert_eq!(content_type(&client, "image/png"), Some(ContentType::JSON));
+        assert_eq!(
+            content_type(
+                &client,
+                "text/html,application/xhtml+xml,application/xml;q=0.9,*/*;q=0.8"
+            ),
+            Some(ContentType::HTML)
+        );
+        assert_eq!(
+            content_type(&client, "text/html;q=0.5, application/problem+json"),
+            problem
+        );
+        assert_eq!(
+            content_type(&client, "application/json;q=0, text/plain;q=0.1"),
+            Some(ContentType::Plain)
+        );
+    }
+
+    #[test]
+    fn bodies() {
+        let client = client(rocket::build());
+
+        let response = client.get("/limited").header(Accept::HTML).dispatch();
+        assert_eq!(response.status(), Status::TooManyRequests);
+        let html = response.into_string().unwrap();
+        assert!(html.contains("<title>429 Too Many Requests</title>"));
+        assert!(html.contains("<p>&lt;slow down&gt;</p>"));
+
+        let response = client.get("/limited").header(Accept::Plain).dispatch();
+        assert_eq!(
+            response.into_string().unwrap(),
+            "429 Too Many Requests: <slow down>"
+        );
+        let response = client.get("/missing").header(Accept::Plain).dispatch();
+        assert_eq!(response.into_string().unwrap(), "404 Not Found");
+
+        let problem = MediaType::new("application", "problem+json");
+        let response = client
+            .get("/limited")
+            .header(Accept::new([problem.into()]))
+            .dispatch();
+        assert_eq!(
+            response.into_json::<Value>().unwrap(),
+            json!({
+                "type": "about:blank",
+                "title": "Too Many Requests",
+                "status": 429,
+                "detail": "<slow down>",
+                "instance": "/limited",
+                "reason": "TOO_MANY_REQUESTS"
+            })
+        );
+    }
+
+    #[test]
+    fn custom_page() {
+        let page = ErrorPage::new(|error| format!("<h1>Oops ({})</h1>", error.status().code));
+        let client = client(rocket::build().manage(page));
+        let response = client.get("/missing").header(Accept::HTML).dispatch();
+        assert_eq!(response.content_type(), Some(ContentType::HTML));
+        assert_eq!(response.into_string().unwrap(), "<h1>Oops (404)</h1>");
+    }
+}
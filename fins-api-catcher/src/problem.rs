@@ -0,0 +1,232 @@
+use crate::{ApiError, RequestId};
+use rocket::catch;
+use rocket::http::{ContentType, Status};
+use rocket::response::{self, Responder};
+use rocket::serde::json::{Json, Value};
+use rocket::Request;
+use serde::Serialize;
+use serde_json::Map;
+use std::borrow::Cow;
+
+#[derive(Debug, Serialize)]
+struct ProblemDetails {
+    #[serde(rename = "type")]
+    type_: Cow<'static, str>,
+    title: &'static str,
+    status: u16,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    detail: Option<Cow<'static, str>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    instance: Option<String>,
+    #[serde(flatten)]
+    extensions: Map<String, Value>,
+}
+
+/// An `application/problem+json` response as described in RFC 9457.
+///
+/// `title` is the reason phrase of the status and `instance` defaults to the request URI.
+///
+/// ```
+/// use fins_api_catcher::Problem;
+/// use rocket::http::Status;
+/// use rocket::serde::json::json;
+///
+/// let problem = Problem::new(Status::Forbidden)
+///     .with_type("https://example.com/probs/out-of-credit")
+///     .with_detail("Your current balance is 30, but that costs 50.")
+///     .with_extension("balance", json!(30));
+/// ```
+#[derive(Debug)]
+pub struct Problem {
+    status: Status,
+    // boxed to keep `Result<T, Problem>` small
+    details: Box<ProblemDetails>,
+}
+
+impl Problem {
+    pub fn new(status: Status) -> Self {
+        Self {
+            status,
+            details: Box::new(ProblemDetails {
+                type_: "about:blank".into(),
+                title: status.reason().unwrap_or("Unknown Error"),
+                status: status.code,
+                detail: None,
+                instance: None,
+                extensions: Map::new(),
+            }),
+        }
+    }
+
+    /// Replaces the default `about:blank` type URI.
+    pub fn with_type(mut self, type_: impl Into<Cow<'static, str>>) -> Self {
+        self.details.type_ = type_.into();
+        self
+    }
+
+    /// A human-readable explanation specific to this occurrence of the problem.
+    pub fn with_detail(mut self, detail: impl Into<Cow<'static, str>>) -> Self {
+        self.details.detail = Some(detail.into());
+        self
+    }
+
+    /// Replaces the request URI as `instance`.
+    pub fn with_instance(mut self, instance: impl Into<String>) -> Self {
+        self.details.instance = Some(instance.into());
+        self
+    }
+
+    /// Adds an extension member, the standard members cannot be replaced this way.
+    pub fn with_extension(mut self, key: impl Into<String>, value: Value) -> Self {
+        let key = key.into();
+        if !matches!(
+            key.as_str(),
+            "type" | "title" | "status" | "detail" | "instance"
+        ) {
+            self.details.extensions.insert(key, value);
+        }
+        self
+    }
+
+    pub fn status(&self) -> Status {
+        self.status
+    }
+}
+
+impl From<Status> for Problem {
+    fn from(status: Status) -> Self {
+        Problem::new(status)
+    }
+}
+
+impl From<ApiError> for Problem {
+    /// Uses the message as `detail` and adds `reason` and `details` as extension members.
+    fn from(error: ApiError) -> Self {
+        let error = error.response.error;
+        let mut problem = Problem::new(Status::new(error.code))
+            .with_detail(error.message)
+            .with_extension("reason", Value::String(error.reason.into_owned()));
+        if let Some(details) = error.details {
+            problem = problem.with_extension("details", details);
+        }
+        problem
+    }
+}
+
+impl<'r> Responder<'r, 'static> for Problem {
+    fn respond_to(mut self, req: &'r Request<'_>) -> response::Result<'static> {
+        if self.details.instance.is_none() {
+            self.details.instance = Some(req.uri().to_string());
+        }
+        if let Some(id) = RequestId::get(req) {
+            self = self.with_extension("request_id", Value::String(id.to_string()));
+        }
+        if let Some(details) = crate::debug::details(self.status, req) {
+            self = self.with_extension("debug", details);
+        }
+        (self.status, (problem_json(), Json(self.details))).respond_to(req)
+    }
+}
+
+/// `application/problem+json`
+pub(crate) fn problem_json() -> ContentType {
+    ContentType::new("application", "problem+json")
+}
+
+/// Like [`crate::api_catcher`], but always responds with `application/problem+json`.
+///
+/// Register it for the mount points that should use problem details,
+/// like `register("/v2", catchers![problem_catcher])`.
+#[catch(default)]
+pub fn problem_catcher(status: Status, req: &Request) -> Problem {
+    match crate::guard_error(status, req) {
+        Some(error) => error.into(),
+        None => status.into(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{api_catcher, problem_catcher, ApiError, Problem};
+    use rocket::http::{ContentType, Status};
+    use rocket::local::blocking::Client;
+    use rocket::serde::json::{json, Value};
+    use rocket::{catchers, get, routes};
+
+    #[get("/credit")]
+    fn credit() -> Result<&'static str, Problem> {
+        Err(Problem::new(Status::Forbidden)
+            .with_type("https://example.com/probs/out-of-credit")
+            .with_detail("Your current balance is 30, but that costs 50.")
+            .with_extension("balance", json!(30))
+            .with_extension("status", json!(200)))
+    }
+
+    fn client() -> Client {
+        let rocket = rocket::build()
+            .mount("/v2", routes![credit])
+            .register("/", catchers![api_catcher])
+            .register("/v2", catchers![problem_catcher]);
+        Client::tracked(rocket).unwrap()
+    }
+
+    #[test]
+    fn catcher() {
+        let client = client();
+        let response = client.get("/v2/missing?page=2").dispatch();
+        assert_eq!(response.status(), Status::NotFound);
+        assert_eq!(
+            response.content_type(),
+            Some(ContentType::new("application", "problem+json"))
+        );
+        assert_eq!(
+            response.into_json::<Value>().unwrap(),
+            json!({
+                "type": "about:blank",
+                "title": "Not Found",
+                "status": 404,
+                "instance": "/v2/missing?page=2"
+            })
+        );
+
+        // other mount points keep the default catcher
+        let response = client.get("/missing").dispatch();
+        assert_eq!(response.content_type(), Some(ContentType::JSON));
+    }
+
+    #[test]
+    fn from_api_error() {
+        let error = ApiError::new(Status::TooManyRequests, "slow down")
+            .with_details(json!({ "limit": 10 }));
+        let details = Problem::from(error).details;
+        assert_eq!(
+            rocket::serde::json::to_value(details).unwrap(),
+            json!({
+                "type": "about:blank",
+                "title": "Too Many Requests",
+                "status": 429,
+                "detail": "slow down",
+                "reason": "TOO_MANY_REQUESTS",
+                "details": { "limit": 10 }
+            })
+        );
+    }
+
+    #[test]
+    fn responder() {
+        let client = client();
+        let response = client.get("/v2/credit").dispatch();
+        assert_eq!(response.status(), Status::Forbidden);
+        assert_eq!(
+            response.into_json::<Value>().unwrap(),
+            json!({
+                "type": "https://example.com/probs/out-of-credit",
+                "title": "Forbidden",
+                "status": 403,
+                "detail": "Your current balance is 30, but that costs 50.",
+                "instance": "/v2/credit",
+                "balance": 30
+            })
+        );
+    }
+}
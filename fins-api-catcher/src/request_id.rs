@@ -0,0 +1,219 @@
+use rocket::fairing::{Fairing, Info, Kind};
+use rocket::http::{Header, Status};
+use rocket::request::{FromRequest, Outcome};
+use rocket::{Data, Request, Response};
+use std::collections::hash_map::RandomState;
+use std::fmt::Display;
+use std::hash::BuildHasher;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::SystemTime;
+
+const REQUEST_ID_HEADER: &str = "X-Request-Id";
+const REQUEST_ID_MAX_LEN: usize = 128;
+
+/// Identifies a request in logs and error bodies, see [`RequestId::fairing`].
+///
+/// Use `&RequestId` as request guard to access it in routes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RequestId(String);
+
+impl RequestId {
+    /// Takes the `X-Request-Id` header of requests or generates an ID if it is missing or invalid,
+    /// echoes it in the `X-Request-Id` response header and logs it for server errors.
+    ///
+    /// The catchers, [`crate::ApiError`] and [`crate::Problem`] include it as `request_id` in their bodies.
+    pub fn fairing() -> impl Fairing {
+        RequestIdFairing
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// The ID of the request, if the fairing is attached.
+    pub fn get<'r>(req: &'r Request<'_>) -> Option<&'r RequestId> {
+        req.local_cache(|| None::<RequestId>).as_ref()
+    }
+
+    /// Visible ASCII only, so it can be echoed and logged as is.
+    fn parse(id: &str) -> Option<Self> {
+        let valid = !id.is_empty()
+            && id.len() <= REQUEST_ID_MAX_LEN
+            && id.bytes().all(|b| b.is_ascii_graphic());
+        valid.then(|| RequestId(id.to_owned()))
+    }
+
+    /// 128 pseudo-random bits as hex, each half from its own [`RandomState`], so they are unrelated.
+    fn generate() -> Self {
+        static COUNTER: AtomicU64 = AtomicU64::new(0);
+        let count = COUNTER.fetch_add(1, Ordering::Relaxed);
+        let now = SystemTime::now();
+        let random = || RandomState::new().hash_one((count, now));
+        let (high, low) = (random(), random());
+        RequestId(format!("{high:016x}{low:016x}"))
+    }
+}
+
+impl AsRef<str> for RequestId {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl Display for RequestId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for &'r RequestId {
+    type Error = ();
+
+    async fn from_request(req: &'r Request<'_>) -> Outcome<Self, Self::Error> {
+        match RequestId::get(req) {
+            Some(id) => Outcome::Success(id),
+            None => {
+                log::error!("requesting `RequestId` without attaching `RequestId::fairing()`.");
+                Outcome::Error((Status::InternalServerError, ()))
+            }
+        }
+    }
+}
+
+struct RequestIdFairing;
+
+#[rocket::async_trait]
+impl Fairing for RequestIdFairing {
+    fn info(&self) -> Info {
+        Info {
+            name: "Request ID",
+            kind: Kind::Request | Kind::Response,
+        }
+    }
+
+    async fn on_request(&self, req: &mut Request<'_>, _data: &mut Data<'_>) {
+        let id = req
+            .headers()
+            .get_one(REQUEST_ID_HEADER)
+            .and_then(RequestId::parse)
+            .unwrap_or_else(RequestId::generate);
+        req.local_cache(|| Some(id));
+    }
+
+    async fn on_response<'r>(&self, req: &'r Request<'_>, res: &mut Response<'r>) {
+        let Some(id) = RequestId::get(req) else {
+            return;
+        };
+        if res.status().class().is_server_error() {
+            log::error!(
+                "{} {} responded with {} (request id {id})",
+                req.method(),
+                req.uri(),
+                res.status()
+            );
+        }
+        res.set_header(Header::new(REQUEST_ID_HEADER, id.0.clone()));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{api_catcher, problem_catcher, ApiError, RequestId};
+    use rocket::http::{Header, Status};
+    use rocket::local::blocking::Client;
+    use rocket::serde::json::{json, Value};
+    use rocket::{catchers, get, routes};
+
+    #[get("/id")]
+    fn id(id: &RequestId) -> String {
+        id.to_string()
+    }
+
+    #[get("/fail")]
+    fn fail() -> ApiError {
+        ApiError::new(Status::ServiceUnavailable, "try again later")
+    }
+
+    fn client() -> Client {
+        let rocket = rocket::build()
+            .attach(RequestId::fairing())
+            .mount("/", routes![id, fail])
+            .register("/", catchers![api_catcher])
+            .register("/v2", catchers![problem_catcher]);
+        Client::tracked(rocket).unwrap()
+    }
+
+    #[test]
+    fn header() {
+        let client = client();
+        let response = client
+            .get("/id")
+            .header(Header::new("X-Request-Id", "abc-123"))
+            .dispatch();
+        assert_eq!(response.headers().get_one("X-Request-Id"), Some("abc-123"));
+        assert_eq!(response.into_string().unwrap(), "abc-123");
+
+        // generated if missing or invalid
+        let response = client.get("/id").dispatch();
+        let id = response
+            .headers()
+            .get_one("X-Request-Id")
+            .unwrap()
+            .to_owned();
+        assert_eq!(id.len(), 32);
+        assert_eq!(response.into_string().unwrap(), id);
+        let response = client
+            .get("/id")
+            .header(Header::new("X-Request-Id", "a b"))
+            .dispatch();
+        let other = response.headers().get_one("X-Request-Id").unwrap();
+        assert_eq!(other.len(), 32);
+        assert_ne!(other, id);
+    }
+
+    #[test]
+    fn error_bodies() {
+        let client = client();
+        let response = client
+            .get("/missing")
+            .header(Header::new("X-Request-Id", "abc-123"))
+            .dispatch();
+        assert_eq!(response.headers().get_one("X-Request-Id"), Some("abc-123"));
+        assert_eq!(
+            response.into_json::<Value>().unwrap(),
+            json!({
+                "error": {
+                    "code": 404,
+                    "message": "Not Found",
+                    "reason": "NOT_FOUND",
+                    "request_id": "abc-123"
+                }
+            })
+        );
+
+        let response = client
+            .get("/fail")
+            .header(Header::new("X-Request-Id", "abc-123"))
+            .dispatch();
+        assert_eq!(response.status(), Status::ServiceUnavailable);
+        let body = response.into_json::<Value>().unwrap();
+        assert_eq!(body["error"]["request_id"], "abc-123");
+
+        let response = client
+            .get("/v2/missing")
+            .header(Header::new("X-Request-Id", "abc-123"))
+            .dispatch();
+        let body = response.into_json::<Value>().unwrap();
+        assert_eq!(body["request_id"], "abc-123");
+    }
+
+    #[test]
+    fn without_fairing() {
+        let rocket = rocket::build().mount("/", routes![id]);
+        let client = Client::tracked(rocket).unwrap();
+        let response = client.get("/id").dispatch();
+        assert_eq!(response.status(), Status::InternalServerError);
+        assert_eq!(response.headers().get_one("X-Request-Id"), None);
+    }
+}
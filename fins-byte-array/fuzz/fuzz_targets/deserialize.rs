@@ -0,0 +1,43 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use serde::de::value::{BytesDeserializer, Error, StrDeserializer};
+
+/// Feeds `data` as bytes and, if it is valid UTF-8, as a string into `deserialize::<[u8; N]>`.
+fn deserialize<const N: usize>(data: &[u8]) {
+    if let Ok(bytes) =
+        fins_byte_array::deserialize::<[u8; N], _, N>(BytesDeserializer::<Error>::new(data))
+    {
+        assert_eq!(bytes, data);
+    }
+    if let Ok(v) = std::str::from_utf8(data) {
+        let result =
+            fins_byte_array::deserialize::<[u8; N], _, N>(StrDeserializer::<Error>::new(v));
+        // the serde error and the non-serde error must agree
+        assert_eq!(result.is_ok(), fins_byte_array::decode::<N>(v).is_ok());
+        let _ =
+            fins_byte_array::hex::deserialize::<[u8; N], _, N>(StrDeserializer::<Error>::new(v));
+        let _ =
+            fins_byte_array::base64::deserialize::<[u8; N], _, N>(StrDeserializer::<Error>::new(v));
+        let _ = fins_byte_array::hex_prefixed::deserialize::<[u8; N], _, N>(
+            StrDeserializer::<Error>::new(v),
+        );
+        let _ =
+            fins_byte_array::base32::deserialize::<[u8; N], _, N>(StrDeserializer::<Error>::new(v));
+        let _ =
+            fins_byte_array::base58::deserialize::<[u8; N], _, N>(StrDeserializer::<Error>::new(v));
+        let _ = fins_byte_array::vec::deserialize::<Vec<u8>, _>(StrDeserializer::<Error>::new(v));
+    }
+}
+
+fuzz_target!(|data: &[u8]| {
+    deserialize::<0>(data);
+    deserialize::<1>(data);
+    deserialize::<2>(data);
+    deserialize::<3>(data);
+    deserialize::<4>(data);
+    deserialize::<5>(data);
+    deserialize::<16>(data);
+    deserialize::<32>(data);
+    deserialize::<33>(data);
+});
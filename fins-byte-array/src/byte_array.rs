@@ -0,0 +1,161 @@
+use crate::{ByteArrayVisitor, Encoding};
+use core::fmt::Display;
+use core::ops::Deref;
+use core::str::FromStr;
+use serde::{de::Visitor, Deserialize, Deserializer, Serialize, Serializer};
+
+/// A fixed number of bytes that (de)serializes like [`crate::deserialize`] and [`crate::serialize`].
+///
+/// Unlike the functions it can be used directly as a field type, including in
+/// `Vec<ByteArray<N>>`, `Option<ByteArray<N>>` or as a map key.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(
+    feature = "diesel",
+    derive(diesel::expression::AsExpression, diesel::deserialize::FromSqlRow)
+)]
+#[cfg_attr(feature = "diesel", diesel(sql_type = diesel::sql_types::Binary))]
+pub struct ByteArray<const N: usize>([u8; N]);
+
+impl<const N: usize> ByteArray<N> {
+    pub const fn new(bytes: [u8; N]) -> Self {
+        Self(bytes)
+    }
+
+    pub const fn into_inner(self) -> [u8; N] {
+        self.0
+    }
+}
+
+impl<const N: usize> Default for ByteArray<N> {
+    fn default() -> Self {
+        Self([0; N])
+    }
+}
+
+impl<const N: usize> Deref for ByteArray<N> {
+    type Target = [u8; N];
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<const N: usize> AsRef<[u8]> for ByteArray<N> {
+    fn as_ref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl<const N: usize> From<[u8; N]> for ByteArray<N> {
+    fn from(bytes: [u8; N]) -> Self {
+        Self(bytes)
+    }
+}
+
+impl<const N: usize> From<ByteArray<N>> for [u8; N] {
+    fn from(bytes: ByteArray<N>) -> Self {
+        bytes.0
+    }
+}
+
+/// Formats the bytes as a lowercase hex string.
+impl<const N: usize> Display for ByteArray<N> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        crate::HexDisplay::new(&self.0).fmt(f)
+    }
+}
+
+/// Parses a hex or base64 string, see [`crate::auto`].
+///
+/// The errors have the same messages as deserializing, use [`crate::decode`] for a [`crate::ByteArrayError`].
+impl<const N: usize> FromStr for ByteArray<N> {
+    type Err = serde::de::value::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        ByteArrayVisitor::<[u8; N]>::new(Encoding::Auto)
+            .visit_str(s)
+            .map(ByteArray)
+    }
+}
+
+impl<const N: usize> Serialize for ByteArray<N> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        crate::serialize(&self.0, serializer)
+    }
+}
+
+impl<'de, const N: usize> Deserialize<'de> for ByteArray<N> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        crate::deserialize(deserializer)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ByteArray;
+    use std::collections::BTreeMap;
+
+    #[test]
+    fn display() {
+        let bytes = ByteArray::new([0xde, 0xad, 0xbe, 0xef]);
+        assert_eq!(bytes.to_string(), "deadbeef");
+        assert_eq!(ByteArray::<0>::default().to_string(), "");
+    }
+
+    #[test]
+    fn from_str() {
+        let bytes: ByteArray<4> = "deadbeef".parse().unwrap();
+        assert_eq!(bytes, ByteArray::new([0xde, 0xad, 0xbe, 0xef]));
+        let bytes: ByteArray<4> = "3q2+7w==".parse().unwrap();
+        assert_eq!(bytes, ByteArray::new([0xde, 0xad, 0xbe, 0xef]));
+
+        let error = "deadbeef".parse::<ByteArray<3>>().unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "invalid length 8, expected bytes or an array of length 3 or a hex string of length 6 or a base64 or base64url string of length 4"
+        );
+    }
+
+    #[test]
+    fn conversions() {
+        let bytes = ByteArray::from([1, 2, 3]);
+        assert_eq!(*bytes, [1, 2, 3]);
+        assert_eq!(bytes.as_ref(), &[1, 2, 3]);
+        assert_eq!(<[u8; 3]>::from(bytes), [1, 2, 3]);
+        assert_eq!(bytes.into_inner(), [1, 2, 3]);
+        assert!(ByteArray::new([1, 2]) < ByteArray::new([1, 3]));
+    }
+
+    #[test]
+    fn serde() {
+        let json = serde_json::to_string(&ByteArray::new([0xde, 0xad])).unwrap();
+        assert_eq!(json, r#""dead""#);
+
+        let value: Vec<ByteArray<2>> = serde_json::from_str(r#"["dead", "AP8=", [1, 2]]"#).unwrap();
+        assert_eq!(
+            value,
+            vec![
+                ByteArray::new([0xde, 0xad]),
+                ByteArray::new([0x00, 0xff]),
+                ByteArray::new([1, 2]),
+            ]
+        );
+
+        let value: Option<ByteArray<2>> = serde_json::from_str("null").unwrap();
+        assert_eq!(value, None);
+        let value: Option<ByteArray<2>> = serde_json::from_str(r#""dead""#).unwrap();
+        assert_eq!(value, Some(ByteArray::new([0xde, 0xad])));
+
+        let map = BTreeMap::from([(ByteArray::new([0xde, 0xad]), 1)]);
+        let json = serde_json::to_string(&map).unwrap();
+        assert_eq!(json, r#"{"dead":1}"#);
+        let value: BTreeMap<ByteArray<2>, u8> = serde_json::from_str(&json).unwrap();
+        assert_eq!(value, map);
+    }
+}
@@ -0,0 +1,119 @@
+//! Crockford base32 without padding or check symbol, see <https://www.crockford.com/base32.html>.
+
+use core::fmt::{self, Display, Write as _};
+
+const ALPHABET: &[u8; 32] = b"0123456789ABCDEFGHJKMNPQRSTVWXYZ";
+
+pub(crate) const fn encoded_size(n: usize) -> usize {
+    (n * 8).div_ceil(5)
+}
+
+pub(crate) enum DecodeError {
+    InvalidCharacter {
+        c: char,
+        index: usize,
+    },
+    /// The unused bits of the last symbol are not zero.
+    InvalidLastSymbol {
+        c: char,
+        index: usize,
+    },
+    OutputSliceTooSmall,
+}
+
+/// Formats bytes as an uppercase Crockford base32 string.
+pub(crate) struct Encoded<'a>(pub(crate) &'a [u8]);
+
+impl Display for Encoded<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut buffer = 0u16;
+        let mut bits = 0;
+        for &byte in self.0 {
+            buffer = (buffer << 8) | u16::from(byte);
+            bits += 8;
+            while bits >= 5 {
+                bits -= 5;
+                f.write_char(ALPHABET[usize::from((buffer >> bits) & 0x1f)] as char)?;
+            }
+        }
+        if bits > 0 {
+            f.write_char(ALPHABET[usize::from((buffer << (5 - bits)) & 0x1f)] as char)?;
+        }
+        Ok(())
+    }
+}
+
+/// Case insensitive, `I` and `L` are read as `1` and `O` is read as `0`.
+fn decode_symbol(c: u8) -> Option<u8> {
+    let value = match c.to_ascii_uppercase() {
+        c @ b'0'..=b'9' => c - b'0',
+        b'O' => 0,
+        b'I' | b'L' => 1,
+        c => ALPHABET.iter().position(|&a| a == c)? as u8,
+    };
+    Some(value)
+}
+
+/// Decodes `v` into `data` and returns the number of decoded bytes.
+pub(crate) fn decode_to_slice(v: &str, data: &mut [u8]) -> Result<usize, DecodeError> {
+    let mut buffer = 0u16;
+    let mut bits = 0;
+    let mut len = 0;
+    for (index, c) in v.bytes().enumerate() {
+        let value = decode_symbol(c).ok_or_else(|| DecodeError::InvalidCharacter {
+            c: v[index..].chars().next().unwrap_or_default(),
+            index,
+        })?;
+        buffer = (buffer << 5) | u16::from(value);
+        bits += 5;
+        if bits >= 8 {
+            bits -= 8;
+            *data.get_mut(len).ok_or(DecodeError::OutputSliceTooSmall)? = (buffer >> bits) as u8;
+            len += 1;
+        }
+        buffer &= (1 << bits) - 1;
+    }
+    if buffer != 0 {
+        return Err(DecodeError::InvalidLastSymbol {
+            c: v.chars().last().unwrap_or_default(),
+            index: v.len() - 1,
+        });
+    }
+    Ok(len)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{decode_to_slice, encoded_size, DecodeError, Encoded};
+
+    #[test]
+    fn round_trip() {
+        for n in 0..=16 {
+            let data: Vec<u8> = (0..n).map(|i| (i * 37 + 11) as u8).collect();
+            let encoded = Encoded(&data).to_string();
+            assert_eq!(encoded.len(), encoded_size(n));
+            let mut decoded = vec![0; n];
+            assert_eq!(decode_to_slice(&encoded, &mut decoded).ok(), Some(n));
+            assert_eq!(decoded, data);
+        }
+    }
+
+    #[test]
+    fn known_values() {
+        assert_eq!(Encoded(&[0xde, 0xad, 0xbe, 0xef]).to_string(), "VTPVXVR");
+        let mut decoded = [0; 4];
+        assert_eq!(decode_to_slice("vtpvxvr", &mut decoded).ok(), Some(4));
+        assert_eq!(decoded, [0xde, 0xad, 0xbe, 0xef]);
+        let mut decoded = [0; 1];
+        assert_eq!(decode_to_slice("o8", &mut decoded).ok(), Some(1));
+        assert_eq!(decoded, [0x02]);
+        assert!(matches!(
+            decode_to_slice("01", &mut decoded),
+            Err(DecodeError::InvalidLastSymbol { c: '1', index: 1 })
+        ));
+        assert!(matches!(
+            decode_to_slice("0U", &mut decoded),
+            Err(DecodeError::InvalidCharacter { c: 'U', index: 1 })
+        ));
+    }
+}
@@ -0,0 +1,98 @@
+use crate::ByteArray;
+use ::diesel::backend::Backend;
+use ::diesel::deserialize::{self, FromSql};
+use ::diesel::serialize::{self, Output, ToSql};
+use ::diesel::sql_types::Binary;
+
+impl<DB, const N: usize> ToSql<Binary, DB> for ByteArray<N>
+where
+    DB: Backend,
+    [u8]: ToSql<Binary, DB>,
+{
+    fn to_sql<'b>(&'b self, out: &mut Output<'b, '_, DB>) -> serialize::Result {
+        self.as_slice().to_sql(out)
+    }
+}
+
+/// Fails if the stored value does not have exactly `N` bytes.
+impl<DB, const N: usize> FromSql<Binary, DB> for ByteArray<N>
+where
+    DB: Backend,
+    Vec<u8>: FromSql<Binary, DB>,
+{
+    fn from_sql(bytes: DB::RawValue<'_>) -> deserialize::Result<Self> {
+        let bytes = Vec::<u8>::from_sql(bytes)?;
+        let len = bytes.len();
+        bytes
+            .try_into()
+            .map(ByteArray::new)
+            .map_err(|_| format!("invalid length {}, expected {} bytes", len, N).into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ByteArray;
+    use diesel::prelude::*;
+    use diesel::sql_types::Binary;
+    use diesel::sqlite::SqliteConnection;
+
+    diesel::table! {
+        objects (id) {
+            id -> Binary,
+        }
+    }
+
+    #[derive(Debug, PartialEq, Queryable, Insertable)]
+    #[diesel(table_name = objects)]
+    struct Object {
+        id: ByteArray<4>,
+    }
+
+    fn connection() -> SqliteConnection {
+        let mut conn = SqliteConnection::establish(":memory:").unwrap();
+        diesel::sql_query("CREATE TABLE objects (id BLOB PRIMARY KEY NOT NULL)")
+            .execute(&mut conn)
+            .unwrap();
+        conn
+    }
+
+    #[test]
+    fn round_trip() {
+        let mut conn = connection();
+        let object = Object {
+            id: ByteArray::new([0xde, 0xad, 0xbe, 0xef]),
+        };
+        diesel::insert_into(objects::table)
+            .values(&object)
+            .execute(&mut conn)
+            .unwrap();
+
+        let loaded: Object = objects::table
+            .filter(objects::id.eq(ByteArray::new([0xde, 0xad, 0xbe, 0xef])))
+            .first(&mut conn)
+            .unwrap();
+        assert_eq!(loaded, object);
+    }
+
+    #[test]
+    fn invalid_length() {
+        let mut conn = connection();
+        diesel::insert_into(objects::table)
+            .values(objects::id.eq(vec![0xde, 0xad]))
+            .execute(&mut conn)
+            .unwrap();
+
+        let error = objects::table
+            .select(objects::id)
+            .first::<ByteArray<4>>(&mut conn)
+            .unwrap_err();
+        let source = std::error::Error::source(&error).unwrap();
+        assert_eq!(source.to_string(), "invalid length 2, expected 4 bytes");
+
+        let value = diesel::select(diesel::dsl::sql::<Binary>("x'deadbeef'"))
+            .get_result::<ByteArray<4>>(&mut conn)
+            .unwrap();
+        assert_eq!(value, ByteArray::new([0xde, 0xad, 0xbe, 0xef]));
+    }
+}
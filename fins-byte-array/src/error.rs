@@ -0,0 +1,90 @@
+use core::fmt::Display;
+use thiserror::Error;
+
+/// The string encoding that was assumed when decoding failed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum StringEncoding {
+    Hex,
+    /// Hex with a `0x` or `0X` prefix.
+    HexPrefixed,
+    Base64,
+    Base64Url,
+    /// Crockford base32.
+    Base32,
+    #[cfg(feature = "base58")]
+    Base58,
+}
+
+impl Display for StringEncoding {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str(match self {
+            StringEncoding::Hex => "hex",
+            StringEncoding::HexPrefixed => "0x-prefixed hex",
+            StringEncoding::Base64 => "base64",
+            StringEncoding::Base64Url => "base64url",
+            StringEncoding::Base32 => "Crockford base32",
+            #[cfg(feature = "base58")]
+            StringEncoding::Base58 => "base58",
+        })
+    }
+}
+
+/// Why bytes could not be decoded, returned by [`crate::decode`] and used for the serde errors.
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ByteArrayError {
+    /// `expected` is in bytes, `actual` is the length of the input: the UTF-8 length of a string
+    /// (the number of decoded bytes for base58), or the number of bytes or sequence elements.
+    #[error("invalid length {actual}, expected {expected} bytes")]
+    InvalidLength { expected: usize, actual: usize },
+    #[error("invalid character `{c}` at index {index}")]
+    InvalidCharacter {
+        c: char,
+        index: usize,
+        encoding: StringEncoding,
+    },
+    /// The unused bits of the last symbol are not zero.
+    #[error("invalid last symbol `{c}` at index {index}")]
+    InvalidLastSymbol {
+        c: char,
+        index: usize,
+        encoding: StringEncoding,
+    },
+    /// The base64 padding from index `from` to `to` (inclusive) is wrong.
+    #[error("invalid padding from index {from} to {to}")]
+    InvalidPadding { from: usize, to: usize },
+    #[error("missing `0x` prefix")]
+    MissingPrefix,
+    /// A sequence has `actual` instead of `expected` elements.
+    #[error("too many sequence elements ({actual}, expected {expected})")]
+    TooManyElements { expected: usize, actual: usize },
+}
+
+impl ByteArrayError {
+    /// Applies `f` to the indices of errors that refer to a position in the input.
+    #[cfg(feature = "alloc")]
+    pub(crate) fn map_index(self, f: impl Fn(usize) -> usize) -> Self {
+        match self {
+            ByteArrayError::InvalidCharacter { c, index, encoding } => {
+                ByteArrayError::InvalidCharacter {
+                    c,
+                    index: f(index),
+                    encoding,
+                }
+            }
+            ByteArrayError::InvalidLastSymbol { c, index, encoding } => {
+                ByteArrayError::InvalidLastSymbol {
+                    c,
+                    index: f(index),
+                    encoding,
+                }
+            }
+            ByteArrayError::InvalidPadding { from, to } => ByteArrayError::InvalidPadding {
+                from: f(from),
+                to: f(to),
+            },
+            error => error,
+        }
+    }
+}
@@ -0,0 +1,44 @@
+/// Integers that are stored as `N` bytes, used by [`crate::be`] and [`crate::le`].
+///
+/// Implemented for all primitive integers except `usize` and `isize`, whose size depends on the platform.
+pub trait Integer<const N: usize>: Copy + sealed::Sealed {
+    fn from_be_bytes(bytes: [u8; N]) -> Self;
+
+    fn from_le_bytes(bytes: [u8; N]) -> Self;
+
+    fn to_be_bytes(self) -> [u8; N];
+
+    fn to_le_bytes(self) -> [u8; N];
+}
+
+mod sealed {
+    pub trait Sealed {}
+}
+
+macro_rules! impl_integer {
+    ($($t:ty),*) => {
+        $(
+            impl sealed::Sealed for $t {}
+
+            impl Integer<{ core::mem::size_of::<$t>() }> for $t {
+                fn from_be_bytes(bytes: [u8; core::mem::size_of::<$t>()]) -> Self {
+                    <$t>::from_be_bytes(bytes)
+                }
+
+                fn from_le_bytes(bytes: [u8; core::mem::size_of::<$t>()]) -> Self {
+                    <$t>::from_le_bytes(bytes)
+                }
+
+                fn to_be_bytes(self) -> [u8; core::mem::size_of::<$t>()] {
+                    <$t>::to_be_bytes(self)
+                }
+
+                fn to_le_bytes(self) -> [u8; core::mem::size_of::<$t>()] {
+                    <$t>::to_le_bytes(self)
+                }
+            }
+        )*
+    };
+}
+
+impl_integer!(u8, u16, u32, u64, u128, i8, i16, i32, i64, i128);
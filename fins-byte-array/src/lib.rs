@@ -1,209 +1,1496 @@
-use base64::{prelude::BASE64_STANDARD_NO_PAD, Engine as _};
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
+
+#[cfg(feature = "alloc")]
+extern crate alloc;
+
+use ::base64::{
+    display::Base64Display,
+    engine::GeneralPurpose,
+    prelude::{BASE64_STANDARD, BASE64_URL_SAFE},
+};
+use core::fmt::{self, Display};
 use serde::{
-    de::{Error, SeqAccess, Unexpected, Visitor},
-    Deserializer,
+    de::{Error, Visitor},
+    Deserializer, Serialize, Serializer,
 };
+use visitor::{ByteArrayVisitor, Encoding};
+#[cfg(any(feature = "alloc", feature = "heapless"))]
+use visitor::{ByteVecVisitor, VarBytes};
 
-struct ByteArrayVisitor<const N: usize>;
+mod byte_array;
+mod crockford;
+#[cfg(feature = "diesel")]
+mod diesel;
+mod error;
+mod integer;
+#[cfg(feature = "rocket")]
+mod rocket;
+#[cfg(feature = "schemars")]
+mod schemars;
+#[cfg(feature = "secret")]
+mod secret;
+mod visitor;
 
-const fn hex_size(n: usize) -> usize {
-    n * 2
-}
+pub use byte_array::ByteArray;
+pub use error::{ByteArrayError, StringEncoding};
+pub use integer::Integer;
+#[cfg(feature = "secret")]
+pub use secret::SecretByteArray;
 
-const fn div_ceil(dividend: usize, diviser: usize) -> usize {
-    dividend.div_ceil(diviser)
+fn deserialize_encoded<'de, T, D, const N: usize>(
+    deserializer: D,
+    encoding: Encoding,
+) -> Result<T, D::Error>
+where
+    D: Deserializer<'de>,
+    [u8; N]: TryInto<T>,
+    <[u8; N] as TryInto<T>>::Error: core::error::Error,
+{
+    deserialize_into(deserializer, ByteArrayVisitor::new(encoding))
 }
 
-const fn base64_padded_size(n: usize) -> usize {
-    div_ceil(n, 3) * 4
+fn deserialize_into<'de, T, D, const N: usize>(
+    deserializer: D,
+    visitor: ByteArrayVisitor<[u8; N]>,
+) -> Result<T, D::Error>
+where
+    D: Deserializer<'de>,
+    [u8; N]: TryInto<T>,
+    <[u8; N] as TryInto<T>>::Error: core::error::Error,
+{
+    let result = deserialize_fixed(deserializer, visitor)?.try_into();
+    result.map_err(Error::custom)
 }
 
-const fn base64_unpadded_size(n: usize) -> usize {
-    div_ceil(n * 4, 3)
+fn deserialize_fixed<'de, V, D>(deserializer: D, visitor: V) -> Result<V::Value, D::Error>
+where
+    V: Visitor<'de>,
+    D: Deserializer<'de>,
+{
+    // non-self-describing formats like bincode do not support `deserialize_any`
+    if deserializer.is_human_readable() {
+        deserializer.deserialize_any(visitor)
+    } else {
+        deserializer.deserialize_bytes(visitor)
+    }
 }
 
-/*
-const fn base64_padding_size(n: usize) -> usize {
-    n * 2 % 3
+#[cfg(any(feature = "alloc", feature = "heapless"))]
+fn deserialize_var<'de, B, D>(deserializer: D, visitor: ByteVecVisitor<B>) -> Result<B, D::Error>
+where
+    B: VarBytes,
+    D: Deserializer<'de>,
+{
+    if deserializer.is_human_readable() {
+        deserializer.deserialize_any(visitor)
+    } else {
+        deserializer.deserialize_byte_buf(visitor)
+    }
 }
-*/
 
-fn base64_check_padding<const N: usize>(v: &str) -> bool {
-    v.as_bytes()[base64_unpadded_size(N)..base64_padded_size(N)]
-        .iter()
-        .all(|&c| c == b'=')
+/// Serializes `encode(value)` for human readable formats and the raw bytes otherwise.
+///
+/// The encoded string is written with `collect_str`, so serializers that support it do not allocate.
+fn serialize_encoded<'a, S, F, E>(
+    value: &'a [u8],
+    serializer: S,
+    encode: F,
+) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+    F: FnOnce(&'a [u8]) -> E,
+    E: Display,
+{
+    if serializer.is_human_readable() {
+        serializer.collect_str(&encode(value))
+    } else {
+        serializer.serialize_bytes(value)
+    }
 }
 
-enum Decoding {
-    Hex,
-    Base64,
+/// Formats bytes as a lowercase hex string with an optional prefix, without allocating.
+struct HexDisplay<'a> {
+    prefix: &'static str,
+    bytes: &'a [u8],
 }
 
-impl<'de, const N: usize> Visitor<'de> for ByteArrayVisitor<N> {
-    type Value = [u8; N];
+impl<'a> HexDisplay<'a> {
+    fn new(bytes: &'a [u8]) -> Self {
+        Self { prefix: "", bytes }
+    }
+}
 
-    fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
-        match N {
-            0 => write!(formatter, "bytes of length 0, an empty array, or an empty string"),
-            2 | 4 => write!(formatter, "bytes or an array of length {} or a hex or base64 string of length {}", N, N * 2),
-            _ => write!(formatter, "bytes or an array of length {} or a hex string of length {} or a base64 string of length {}", N, hex_size(N), base64_padded_size(N)),
+impl Display for HexDisplay<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.prefix)?;
+        let mut buffer = [0; 64];
+        for chunk in self.bytes.chunks(buffer.len() / 2) {
+            let buffer = &mut buffer[..chunk.len() * 2];
+            ::hex::encode_to_slice(chunk, buffer).map_err(|_| fmt::Error)?;
+            f.write_str(core::str::from_utf8(buffer).map_err(|_| fmt::Error)?)?;
         }
+        Ok(())
     }
+}
+
+fn serialize_hex<T, S>(value: &T, serializer: S) -> Result<S::Ok, S::Error>
+where
+    T: AsRef<[u8]> + ?Sized,
+    S: Serializer,
+{
+    serialize_encoded(value.as_ref(), serializer, HexDisplay::new)
+}
+
+fn serialize_base64<T, S>(
+    value: &T,
+    serializer: S,
+    engine: &GeneralPurpose,
+) -> Result<S::Ok, S::Error>
+where
+    T: AsRef<[u8]> + ?Sized,
+    S: Serializer,
+{
+    serialize_encoded(value.as_ref(), serializer, |value| {
+        Base64Display::new(value, engine)
+    })
+}
+
+/// Deserializes bytes, a sequence of bytes, or a hex or base64 string depending on its length.
+///
+/// This is the same as [`auto::deserialize`].
+pub fn deserialize<'de, T, D, const N: usize>(deserializer: D) -> Result<T, D::Error>
+where
+    D: Deserializer<'de>,
+    [u8; N]: TryInto<T>,
+    <[u8; N] as TryInto<T>>::Error: core::error::Error,
+{
+    deserialize_encoded(deserializer, Encoding::Auto)
+}
 
-    fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
+/// Decodes a hex or base64 string like [`deserialize`], but without serde.
+///
+/// ```
+/// assert_eq!(fins_byte_array::decode::<2>("dead"), Ok([0xde, 0xad]));
+/// assert_eq!(fins_byte_array::decode::<2>("3q0"), Ok([0xde, 0xad]));
+/// assert!(fins_byte_array::decode::<2>("deadbeef").is_err());
+/// ```
+pub fn decode<const N: usize>(v: &str) -> Result<[u8; N], ByteArrayError> {
+    ByteArrayVisitor::<[u8; N]>::new(Encoding::Auto).decode(v)
+}
+
+/// Like [`decode`], but ignores whitespace and `:` separators, see [`lenient`].
+///
+/// ```
+/// assert_eq!(
+///     fins_byte_array::decode_lenient::<4>("DE:ad:BE:ef\n"),
+///     Ok([0xde, 0xad, 0xbe, 0xef])
+/// );
+/// ```
+#[cfg(feature = "alloc")]
+pub fn decode_lenient<const N: usize>(v: &str) -> Result<[u8; N], ByteArrayError> {
+    ByteArrayVisitor::<[u8; N]>::new(Encoding::Auto)
+        .lenient()
+        .decode(v)
+}
+
+/// Serializes bytes as a lowercase hex string for human readable formats and as raw bytes otherwise.
+///
+/// Together with [`deserialize`] this allows using `#[serde(with = "fins_byte_array")]`.
+pub fn serialize<T, S>(value: &T, serializer: S) -> Result<S::Ok, S::Error>
+where
+    T: AsRef<[u8]> + ?Sized,
+    S: Serializer,
+{
+    serialize_hex(value, serializer)
+}
+
+/// Accepts hex and base64 strings, picking the encoding from the length of the string.
+/// Base64 strings may use either the standard or the URL-safe alphabet.
+///
+/// Use with `#[serde(with = "fins_byte_array::auto")]`.
+pub mod auto {
+    use crate::Encoding;
+    use serde::{Deserializer, Serializer};
+
+    pub fn deserialize<'de, T, D, const N: usize>(deserializer: D) -> Result<T, D::Error>
     where
-        E: Error,
+        D: Deserializer<'de>,
+        [u8; N]: TryInto<T>,
+        <[u8; N] as TryInto<T>>::Error: core::error::Error,
     {
-        let mut data = [0; N];
-        let decoding: Option<Decoding> = match N {
-            0 => None,
-            2 | 4 => {
-                if v.len() != N * 2 {
-                    return Err(Error::invalid_length(v.len(), &self));
-                } else if base64_check_padding::<N>(v) {
-                    Some(Decoding::Base64)
-                } else {
-                    Some(Decoding::Hex)
-                }
-            }
-            _ => {
-                if v.len() == base64_padded_size(N) {
-                    if base64_check_padding::<N>(v) {
-                        Some(Decoding::Base64)
-                    } else {
-                        return Err(Error::invalid_value(
-                            Unexpected::Other(&format!(
-                                "invalid padding from index {} to {}",
-                                base64_unpadded_size(N),
-                                base64_padded_size(N) - 1
-                            )),
-                            &self,
-                        ));
-                    }
-                } else if v.len() == hex_size(N) {
-                    Some(Decoding::Hex)
-                } else {
-                    return Err(Error::invalid_length(v.len(), &self));
-                }
-            }
-        };
-        let len =
-            match decoding {
-                None => Ok(0),
-                Some(Decoding::Base64) => BASE64_STANDARD_NO_PAD
-                    .decode_slice(v, &mut data)
-                    .map_err(|e| match e {
-                        base64::DecodeSliceError::DecodeError(
-                            base64::DecodeError::InvalidByte(index, data),
-                        ) => Error::invalid_value(
-                            Unexpected::Other(&format!(
-                                "invalid byte {:#x} at index {}",
-                                data, index
-                            )),
-                            &self,
-                        ),
-                        base64::DecodeSliceError::DecodeError(
-                            base64::DecodeError::InvalidPadding,
-                        ) => Error::invalid_value(Unexpected::Other("invalid padding"), &self),
-                        base64::DecodeSliceError::DecodeError(
-                            base64::DecodeError::InvalidLength(_),
-                        ) => Error::invalid_length(v.len(), &self),
-                        base64::DecodeSliceError::DecodeError(
-                            base64::DecodeError::InvalidLastSymbol(index, data),
-                        ) => Error::invalid_value(
-                            Unexpected::Other(&format!(
-                                "invalid last symbol {:#x} at index {}",
-                                data, index
-                            )),
-                            &self,
-                        ),
-                        base64::DecodeSliceError::OutputSliceTooSmall => {
-                            Error::invalid_length(v.len(), &self)
-                        }
-                    }),
-                Some(Decoding::Hex) => {
-                    hex::decode_to_slice(v, &mut data)
-                        .map(|()| N)
-                        .map_err(|e| match e {
-                            hex::FromHexError::InvalidHexCharacter { c, index } => {
-                                Error::invalid_value(
-                                    Unexpected::Other(&format!(
-                                        "invalid character `{}` at index {}",
-                                        c, index
-                                    )),
-                                    &self,
-                                )
-                            }
-                            hex::FromHexError::OddLength => Error::invalid_length(v.len(), &self),
-                            hex::FromHexError::InvalidStringLength => {
-                                Error::invalid_length(v.len(), &self)
-                            }
-                        })
-                }
-            }?;
-        if len != N {
-            return Err(Error::invalid_length(v.len(), &self));
+        crate::deserialize_encoded(deserializer, Encoding::Auto)
+    }
+
+    /// Serializes bytes as a lowercase hex string for human readable formats and as raw bytes otherwise.
+    pub fn serialize<T, S>(value: &T, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        T: AsRef<[u8]> + ?Sized,
+        S: Serializer,
+    {
+        crate::serialize_hex(value, serializer)
+    }
+}
+
+/// Accepts hex strings only, base64 strings are rejected.
+///
+/// Use with `#[serde(with = "fins_byte_array::hex")]`.
+pub mod hex {
+    use crate::Encoding;
+    use serde::{Deserializer, Serializer};
+
+    pub fn deserialize<'de, T, D, const N: usize>(deserializer: D) -> Result<T, D::Error>
+    where
+        D: Deserializer<'de>,
+        [u8; N]: TryInto<T>,
+        <[u8; N] as TryInto<T>>::Error: core::error::Error,
+    {
+        crate::deserialize_encoded(deserializer, Encoding::Hex)
+    }
+
+    /// Serializes bytes as a lowercase hex string for human readable formats and as raw bytes otherwise.
+    pub fn serialize<T, S>(value: &T, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        T: AsRef<[u8]> + ?Sized,
+        S: Serializer,
+    {
+        crate::serialize_hex(value, serializer)
+    }
+}
+
+/// Accepts padded or unpadded base64 strings using the standard alphabet only, hex strings are rejected.
+///
+/// Use with `#[serde(with = "fins_byte_array::base64")]`.
+pub mod base64 {
+    use crate::Encoding;
+    use serde::{Deserializer, Serializer};
+
+    pub fn deserialize<'de, T, D, const N: usize>(deserializer: D) -> Result<T, D::Error>
+    where
+        D: Deserializer<'de>,
+        [u8; N]: TryInto<T>,
+        <[u8; N] as TryInto<T>>::Error: core::error::Error,
+    {
+        crate::deserialize_encoded(deserializer, Encoding::Base64)
+    }
+
+    /// Serializes bytes as a padded base64 string for human readable formats and as raw bytes otherwise.
+    pub fn serialize<T, S>(value: &T, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        T: AsRef<[u8]> + ?Sized,
+        S: Serializer,
+    {
+        crate::serialize_base64(value, serializer, &crate::BASE64_STANDARD)
+    }
+}
+
+/// Accepts padded or unpadded base64 strings using the URL-safe alphabet (`-` and `_`) only.
+///
+/// Use with `#[serde(with = "fins_byte_array::base64_url")]`.
+pub mod base64_url {
+    use crate::Encoding;
+    use serde::{Deserializer, Serializer};
+
+    pub fn deserialize<'de, T, D, const N: usize>(deserializer: D) -> Result<T, D::Error>
+    where
+        D: Deserializer<'de>,
+        [u8; N]: TryInto<T>,
+        <[u8; N] as TryInto<T>>::Error: core::error::Error,
+    {
+        crate::deserialize_encoded(deserializer, Encoding::Base64Url)
+    }
+
+    /// Serializes bytes as a padded URL-safe base64 string for human readable formats and as raw bytes otherwise.
+    pub fn serialize<T, S>(value: &T, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        T: AsRef<[u8]> + ?Sized,
+        S: Serializer,
+    {
+        crate::serialize_base64(value, serializer, &crate::BASE64_URL_SAFE)
+    }
+}
+
+/// Accepts hex strings with a `0x` or `0X` prefix only.
+///
+/// Use with `#[serde(with = "fins_byte_array::hex_prefixed")]`.
+pub mod hex_prefixed {
+    use crate::Encoding;
+    use serde::{Deserializer, Serializer};
+
+    pub fn deserialize<'de, T, D, const N: usize>(deserializer: D) -> Result<T, D::Error>
+    where
+        D: Deserializer<'de>,
+        [u8; N]: TryInto<T>,
+        <[u8; N] as TryInto<T>>::Error: core::error::Error,
+    {
+        crate::deserialize_encoded(deserializer, Encoding::HexPrefixed)
+    }
+
+    /// Serializes bytes as a `0x`-prefixed lowercase hex string for human readable formats and as raw bytes otherwise.
+    pub fn serialize<T, S>(value: &T, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        T: AsRef<[u8]> + ?Sized,
+        S: Serializer,
+    {
+        crate::serialize_encoded(value.as_ref(), serializer, |bytes| crate::HexDisplay {
+            prefix: "0x",
+            bytes,
+        })
+    }
+}
+
+/// Accepts unpadded Crockford base32 strings only.
+///
+/// Decoding is case insensitive and reads `I` and `L` as `1` and `O` as `0`.
+///
+/// Use with `#[serde(with = "fins_byte_array::base32")]`.
+pub mod base32 {
+    use crate::Encoding;
+    use serde::{Deserializer, Serializer};
+
+    pub fn deserialize<'de, T, D, const N: usize>(deserializer: D) -> Result<T, D::Error>
+    where
+        D: Deserializer<'de>,
+        [u8; N]: TryInto<T>,
+        <[u8; N] as TryInto<T>>::Error: core::error::Error,
+    {
+        crate::deserialize_encoded(deserializer, Encoding::Base32)
+    }
+
+    /// Serializes bytes as an uppercase Crockford base32 string for human readable formats and as raw bytes otherwise.
+    pub fn serialize<T, S>(value: &T, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        T: AsRef<[u8]> + ?Sized,
+        S: Serializer,
+    {
+        crate::serialize_encoded(value.as_ref(), serializer, crate::crockford::Encoded)
+    }
+}
+
+/// Accepts base58 strings (Bitcoin alphabet) only.
+///
+/// Use with `#[serde(with = "fins_byte_array::base58")]`.
+#[cfg(feature = "base58")]
+pub mod base58 {
+    use crate::Encoding;
+    use serde::{Deserializer, Serializer};
+
+    pub fn deserialize<'de, T, D, const N: usize>(deserializer: D) -> Result<T, D::Error>
+    where
+        D: Deserializer<'de>,
+        [u8; N]: TryInto<T>,
+        <[u8; N] as TryInto<T>>::Error: core::error::Error,
+    {
+        crate::deserialize_encoded(deserializer, Encoding::Base58)
+    }
+
+    /// Serializes bytes as a base58 string for human readable formats and as raw bytes otherwise.
+    pub fn serialize<T, S>(value: &T, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        T: AsRef<[u8]> + ?Sized,
+        S: Serializer,
+    {
+        crate::serialize_encoded(value.as_ref(), serializer, |value| {
+            bs58::encode(value).into_string()
+        })
+    }
+}
+
+/// Accepts the same inputs as [`auto`], but ignores whitespace and `:` separators in strings,
+/// e.g. `de:ad:be:ef` or line-wrapped base64. Hex is case insensitive in every mode.
+///
+/// `-` and `_` are kept since they are part of the URL-safe base64 alphabet.
+/// Indices in errors refer to the original string, lengths to the string without separators.
+///
+/// Use with `#[serde(with = "fins_byte_array::lenient")]`.
+#[cfg(feature = "alloc")]
+pub mod lenient {
+    use crate::{ByteArrayVisitor, Encoding};
+    use serde::{Deserializer, Serializer};
+
+    pub fn deserialize<'de, T, D, const N: usize>(deserializer: D) -> Result<T, D::Error>
+    where
+        D: Deserializer<'de>,
+        [u8; N]: TryInto<T>,
+        <[u8; N] as TryInto<T>>::Error: core::error::Error,
+    {
+        crate::deserialize_into(
+            deserializer,
+            ByteArrayVisitor::<[u8; N]>::new(Encoding::Auto).lenient(),
+        )
+    }
+
+    /// Serializes bytes as a lowercase hex string for human readable formats and as raw bytes otherwise.
+    pub fn serialize<T, S>(value: &T, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        T: AsRef<[u8]> + ?Sized,
+        S: Serializer,
+    {
+        crate::serialize_hex(value, serializer)
+    }
+}
+
+/// Serializes the bytes of `T` as a lowercase hex string, see [`serialize`].
+struct HexBytes<'a, T: ?Sized>(&'a T);
+
+impl<T> Serialize for HexBytes<'_, T>
+where
+    T: AsRef<[u8]> + ?Sized,
+{
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serialize_hex(self.0, serializer)
+    }
+}
+
+/// Deserializes an optional value from the same inputs as [`deserialize`], `null` becomes `None`.
+///
+/// Use with `#[serde(default, with = "fins_byte_array::option")]`,
+/// `default` is needed to treat a missing field as `None`.
+pub mod option {
+    use crate::{ByteArray, HexBytes};
+    use serde::{de::Error, Deserialize, Deserializer, Serializer};
+
+    pub fn deserialize<'de, T, D, const N: usize>(deserializer: D) -> Result<Option<T>, D::Error>
+    where
+        D: Deserializer<'de>,
+        [u8; N]: TryInto<T>,
+        <[u8; N] as TryInto<T>>::Error: core::error::Error,
+    {
+        Option::<ByteArray<N>>::deserialize(deserializer)?
+            .map(|bytes| bytes.into_inner().try_into().map_err(Error::custom))
+            .transpose()
+    }
+
+    /// Serializes `Some` like [`crate::serialize`] and `None` as `null`.
+    pub fn serialize<T, S>(value: &Option<T>, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        T: AsRef<[u8]>,
+        S: Serializer,
+    {
+        match value {
+            Some(value) => serializer.serialize_some(&HexBytes(value)),
+            None => serializer.serialize_none(),
         }
-        Ok(data)
     }
+}
+
+/// Deserializes a sequence of values, each from the same inputs as [`deserialize`].
+///
+/// Use with `#[serde(with = "fins_byte_array::seq")]`.
+#[cfg(feature = "alloc")]
+pub mod seq {
+    use crate::{ByteArray, HexBytes};
+    use alloc::vec::Vec;
+    use serde::{de::Error, Deserialize, Deserializer, Serializer};
 
-    fn visit_bytes<E>(self, v: &[u8]) -> Result<Self::Value, E>
+    pub fn deserialize<'de, T, D, const N: usize>(deserializer: D) -> Result<Vec<T>, D::Error>
     where
-        E: Error,
+        D: Deserializer<'de>,
+        [u8; N]: TryInto<T>,
+        <[u8; N] as TryInto<T>>::Error: core::error::Error,
     {
-        v.try_into()
-            .map_err(|_| Error::invalid_length(v.len(), &self))
+        Vec::<ByteArray<N>>::deserialize(deserializer)?
+            .into_iter()
+            .map(|bytes| bytes.into_inner().try_into().map_err(Error::custom))
+            .collect()
     }
 
-    fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+    /// Serializes each element like [`crate::serialize`].
+    pub fn serialize<T, S>(value: &[T], serializer: S) -> Result<S::Ok, S::Error>
     where
-        A: SeqAccess<'de>,
+        T: AsRef<[u8]>,
+        S: Serializer,
     {
-        if let Some(len) = seq.size_hint() {
-            if len != N {
-                return Err(Error::invalid_length(len, &self));
-            }
+        serializer.collect_seq(value.iter().map(HexBytes))
+    }
+}
+
+/// Deserializes a variable number of bytes from hex strings, bytes or arrays.
+///
+/// Works with every type implementing `From<Vec<u8>>` like `Vec<u8>`, `Box<[u8]>` or `bytes::Bytes`.
+/// Unlike [`deserialize`], strings are only read as hex: without a fixed length, strings like `AAAA`
+/// are valid hex and base64, so base64 strings need [`vec::base64`] or
+/// [`vec::base64_url`] instead.
+///
+/// Use with `#[serde(with = "fins_byte_array::vec")]`.
+#[cfg(feature = "alloc")]
+pub mod vec {
+    use crate::{ByteVecVisitor, Encoding};
+    use alloc::vec::Vec;
+    use serde::{Deserializer, Serializer};
+
+    pub fn deserialize<'de, T, D>(deserializer: D) -> Result<T, D::Error>
+    where
+        D: Deserializer<'de>,
+        T: From<Vec<u8>>,
+    {
+        deserialize_bounded::<T, D, 0, { usize::MAX }>(deserializer)
+    }
+
+    /// Like [`deserialize`], but fails if the number of bytes is not between `MIN` and `MAX` (inclusive).
+    ///
+    /// Use with `#[serde(deserialize_with = "fins_byte_array::vec::deserialize_bounded::<_, _, 16, 64>")]`.
+    pub fn deserialize_bounded<'de, T, D, const MIN: usize, const MAX: usize>(
+        deserializer: D,
+    ) -> Result<T, D::Error>
+    where
+        D: Deserializer<'de>,
+        T: From<Vec<u8>>,
+    {
+        deserialize_encoded::<T, D, MIN, MAX>(deserializer, Encoding::Hex)
+    }
+
+    fn deserialize_encoded<'de, T, D, const MIN: usize, const MAX: usize>(
+        deserializer: D,
+        encoding: Encoding,
+    ) -> Result<T, D::Error>
+    where
+        D: Deserializer<'de>,
+        T: From<Vec<u8>>,
+    {
+        let visitor = ByteVecVisitor::<Vec<u8>>::new(encoding, MIN, MAX);
+        crate::deserialize_var(deserializer, visitor).map(T::from)
+    }
+
+    /// Serializes bytes as a lowercase hex string for human readable formats and as raw bytes otherwise.
+    pub fn serialize<T, S>(value: &T, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        T: AsRef<[u8]> + ?Sized,
+        S: Serializer,
+    {
+        crate::serialize_hex(value, serializer)
+    }
+
+    /// Like [`crate::vec`], but strings are read as padded or unpadded base64 using the standard alphabet.
+    ///
+    /// Use with `#[serde(with = "fins_byte_array::vec::base64")]`.
+    pub mod base64 {
+        use crate::Encoding;
+        use alloc::vec::Vec;
+        use serde::{Deserializer, Serializer};
+
+        pub fn deserialize<'de, T, D>(deserializer: D) -> Result<T, D::Error>
+        where
+            D: Deserializer<'de>,
+            T: From<Vec<u8>>,
+        {
+            deserialize_bounded::<T, D, 0, { usize::MAX }>(deserializer)
         }
 
-        let mut data = [0; N];
-        for (i, data) in data.iter_mut().enumerate() {
-            if let Some(item) = seq.next_element::<u8>()? {
-                *data = item;
-            } else {
-                return Err(Error::invalid_length(i, &self));
-            }
+        /// Like [`deserialize`], but fails if the number of bytes is not between `MIN` and `MAX` (inclusive).
+        pub fn deserialize_bounded<'de, T, D, const MIN: usize, const MAX: usize>(
+            deserializer: D,
+        ) -> Result<T, D::Error>
+        where
+            D: Deserializer<'de>,
+            T: From<Vec<u8>>,
+        {
+            super::deserialize_encoded::<T, D, MIN, MAX>(deserializer, Encoding::Base64)
+        }
+
+        /// Serializes bytes like [`crate::base64::serialize`].
+        pub fn serialize<T, S>(value: &T, serializer: S) -> Result<S::Ok, S::Error>
+        where
+            T: AsRef<[u8]> + ?Sized,
+            S: Serializer,
+        {
+            crate::base64::serialize(value, serializer)
         }
+    }
 
-        let mut too_many_elements = 0;
+    /// Like [`crate::vec`], but strings are read as padded or unpadded base64 using the URL-safe alphabet.
+    ///
+    /// Use with `#[serde(with = "fins_byte_array::vec::base64_url")]`.
+    pub mod base64_url {
+        use crate::Encoding;
+        use alloc::vec::Vec;
+        use serde::{Deserializer, Serializer};
 
-        while seq.next_element::<u8>()?.is_some() {
-            too_many_elements += 1;
+        pub fn deserialize<'de, T, D>(deserializer: D) -> Result<T, D::Error>
+        where
+            D: Deserializer<'de>,
+            T: From<Vec<u8>>,
+        {
+            deserialize_bounded::<T, D, 0, { usize::MAX }>(deserializer)
         }
 
-        if too_many_elements > 0 {
-            return Err(Error::invalid_length(N + too_many_elements, &self));
+        /// Like [`deserialize`], but fails if the number of bytes is not between `MIN` and `MAX` (inclusive).
+        pub fn deserialize_bounded<'de, T, D, const MIN: usize, const MAX: usize>(
+            deserializer: D,
+        ) -> Result<T, D::Error>
+        where
+            D: Deserializer<'de>,
+            T: From<Vec<u8>>,
+        {
+            super::deserialize_encoded::<T, D, MIN, MAX>(deserializer, Encoding::Base64Url)
         }
 
-        Ok(data)
+        /// Serializes bytes like [`crate::base64_url::serialize`].
+        pub fn serialize<T, S>(value: &T, serializer: S) -> Result<S::Ok, S::Error>
+        where
+            T: AsRef<[u8]> + ?Sized,
+            S: Serializer,
+        {
+            crate::base64_url::serialize(value, serializer)
+        }
     }
 }
 
-pub fn deserialize<'de, T, D, const N: usize>(deserializer: D) -> Result<T, D::Error>
-where
-    D: Deserializer<'de>,
-    [u8; N]: TryInto<T>,
-    <[u8; N] as TryInto<T>>::Error: std::error::Error,
-{
-    let byte_array = deserializer.deserialize_any(ByteArrayVisitor::<N>)?;
-    let result = byte_array.try_into();
-    result.map_err(Error::custom)
+/// Deserializes a primitive integer from its big-endian bytes, accepting the same inputs as [`deserialize`].
+///
+/// Use with `#[serde(with = "fins_byte_array::be")]`, see [`Integer`] for the supported types.
+pub mod be {
+    use crate::{ByteArrayVisitor, Encoding, Integer};
+    use serde::{Deserializer, Serializer};
+
+    pub fn deserialize<'de, T, D, const N: usize>(deserializer: D) -> Result<T, D::Error>
+    where
+        D: Deserializer<'de>,
+        T: Integer<N>,
+    {
+        let visitor = ByteArrayVisitor::<[u8; N]>::new(Encoding::Auto);
+        crate::deserialize_fixed(deserializer, visitor).map(T::from_be_bytes)
+    }
+
+    /// Serializes the big-endian bytes like [`crate::serialize`].
+    pub fn serialize<T, S, const N: usize>(value: &T, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        T: Integer<N>,
+        S: Serializer,
+    {
+        crate::serialize_hex(&value.to_be_bytes(), serializer)
+    }
+}
+
+/// Deserializes a primitive integer from its little-endian bytes, accepting the same inputs as [`deserialize`].
+///
+/// Use with `#[serde(with = "fins_byte_array::le")]`, see [`Integer`] for the supported types.
+pub mod le {
+    use crate::{ByteArrayVisitor, Encoding, Integer};
+    use serde::{Deserializer, Serializer};
+
+    pub fn deserialize<'de, T, D, const N: usize>(deserializer: D) -> Result<T, D::Error>
+    where
+        D: Deserializer<'de>,
+        T: Integer<N>,
+    {
+        let visitor = ByteArrayVisitor::<[u8; N]>::new(Encoding::Auto);
+        crate::deserialize_fixed(deserializer, visitor).map(T::from_le_bytes)
+    }
+
+    /// Serializes the little-endian bytes like [`crate::serialize`].
+    pub fn serialize<T, S, const N: usize>(value: &T, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        T: Integer<N>,
+        S: Serializer,
+    {
+        crate::serialize_hex(&value.to_le_bytes(), serializer)
+    }
+}
+
+/// Deserializes a [`::uuid::Uuid`] from the same inputs as [`deserialize`].
+///
+/// The hyphenated form is not accepted, use the serde support of `uuid` for that.
+///
+/// Use with `#[serde(with = "fins_byte_array::uuid")]`.
+#[cfg(feature = "uuid")]
+pub mod uuid {
+    use crate::{ByteArrayVisitor, Encoding};
+    use ::uuid::Uuid;
+    use serde::{Deserializer, Serializer};
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<Uuid, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let visitor = ByteArrayVisitor::new(Encoding::Auto);
+        crate::deserialize_fixed(deserializer, visitor).map(Uuid::from_bytes)
+    }
+
+    /// Serializes the bytes like [`crate::serialize`].
+    pub fn serialize<S>(value: &Uuid, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        crate::serialize_hex(value.as_bytes(), serializer)
+    }
+}
+
+/// Deserializes a [`::generic_array::GenericArray`] of bytes from the same inputs as [`deserialize`].
+///
+/// Use with `#[serde(with = "fins_byte_array::generic_array")]`.
+#[cfg(feature = "generic-array")]
+pub mod generic_array {
+    use crate::{ByteArrayVisitor, Encoding};
+    use ::generic_array::{ArrayLength, GenericArray};
+    use serde::{Deserializer, Serializer};
+
+    pub fn deserialize<'de, D, L>(deserializer: D) -> Result<GenericArray<u8, L>, D::Error>
+    where
+        D: Deserializer<'de>,
+        L: ArrayLength,
+    {
+        crate::deserialize_fixed(deserializer, ByteArrayVisitor::new(Encoding::Auto))
+    }
+
+    /// Serializes the bytes like [`crate::serialize`].
+    pub fn serialize<L, S>(value: &GenericArray<u8, L>, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        L: ArrayLength,
+        S: Serializer,
+    {
+        crate::serialize_hex(value.as_slice(), serializer)
+    }
+}
+
+/// Deserializes a [`::heapless::Vec`] of up to `N` bytes from hex strings, bytes or arrays like `vec`.
+///
+/// Use with `#[serde(with = "fins_byte_array::heapless")]`.
+#[cfg(feature = "heapless")]
+pub mod heapless {
+    use crate::{ByteVecVisitor, Encoding};
+    use ::heapless::Vec;
+    use serde::{Deserializer, Serializer};
+
+    pub fn deserialize<'de, D, const N: usize>(deserializer: D) -> Result<Vec<u8, N>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        crate::deserialize_var(deserializer, ByteVecVisitor::new(Encoding::Hex, 0, N))
+    }
+
+    /// Serializes the bytes like [`crate::serialize`].
+    pub fn serialize<S, const N: usize>(
+        value: &Vec<u8, N>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        crate::serialize_hex(value.as_slice(), serializer)
+    }
 }
 
 #[cfg(test)]
 mod tests {
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Hex<const N: usize>(#[serde(with = "crate")] [u8; N]);
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Base64<const N: usize>(
+        #[serde(
+            serialize_with = "crate::base64::serialize",
+            deserialize_with = "crate::deserialize"
+        )]
+        [u8; N],
+    );
+
+    #[derive(Debug, PartialEq, Deserialize)]
+    struct OnlyHex<const N: usize>(#[serde(with = "crate::hex")] [u8; N]);
+
+    #[derive(Debug, PartialEq, Deserialize)]
+    struct OnlyBase64<const N: usize>(#[serde(with = "crate::base64")] [u8; N]);
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Base64Url<const N: usize>(#[serde(with = "crate::base64_url")] [u8; N]);
+
+    fn bytes<const N: usize>() -> [u8; N] {
+        std::array::from_fn(|i| (i * 37 + 11) as u8)
+    }
+
     #[test]
     fn it_works() {
         let result = 2 + 2;
         assert_eq!(result, 4);
     }
+
+    #[test]
+    fn serialize_hex() {
+        let json = serde_json::to_string(&Hex([0xde, 0xad, 0xbe, 0xef])).unwrap();
+        assert_eq!(json, r#""deadbeef""#);
+        let json = serde_json::to_string(&Hex([])).unwrap();
+        assert_eq!(json, r#""""#);
+    }
+
+    #[test]
+    fn serialize_base64() {
+        let json = serde_json::to_string(&Base64([0xde, 0xad, 0xbe, 0xef])).unwrap();
+        assert_eq!(json, r#""3q2+7w==""#);
+        let json = serde_json::to_string(&Base64([0xde, 0xad, 0xbe])).unwrap();
+        assert_eq!(json, r#""3q2+""#);
+    }
+
+    fn round_trip<const N: usize>() {
+        let hex = Hex(bytes::<N>());
+        let json = serde_json::to_string(&hex).unwrap();
+        assert_eq!(
+            serde_json::from_str::<Hex<N>>(&json).unwrap(),
+            hex,
+            "{json}"
+        );
+
+        let base64 = Base64(bytes::<N>());
+        let json = serde_json::to_string(&base64).unwrap();
+        assert_eq!(
+            serde_json::from_str::<Base64<N>>(&json).unwrap(),
+            base64,
+            "{json}"
+        );
+    }
+
+    #[test]
+    fn serialize_round_trip() {
+        round_trip::<0>();
+        round_trip::<1>();
+        round_trip::<2>();
+        round_trip::<3>();
+        round_trip::<4>();
+        round_trip::<5>();
+        round_trip::<16>();
+        round_trip::<20>();
+        round_trip::<32>();
+    }
+
+    #[test]
+    fn hex_only() {
+        let value: OnlyHex<4> = serde_json::from_str(r#""deadbeef""#).unwrap();
+        assert_eq!(value, OnlyHex([0xde, 0xad, 0xbe, 0xef]));
+        let value: OnlyHex<2> = serde_json::from_str(r#""00ff""#).unwrap();
+        assert_eq!(value, OnlyHex([0x00, 0xff]));
+
+        // base64 of the same length is still decoded as hex
+        let error = serde_json::from_str::<OnlyHex<4>>(r#""3q2+7w==""#).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "invalid value: invalid character `q` at index 1, expected bytes or an array of length 4 or a hex string of length 8 at line 1 column 10"
+        );
+        let error = serde_json::from_str::<OnlyHex<3>>(r#""3q2+""#).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "invalid length 4, expected bytes or an array of length 3 or a hex string of length 6 at line 1 column 6"
+        );
+    }
+
+    #[test]
+    fn base64_only() {
+        let value: OnlyBase64<4> = serde_json::from_str(r#""3q2+7w==""#).unwrap();
+        assert_eq!(value, OnlyBase64([0xde, 0xad, 0xbe, 0xef]));
+        let value: OnlyBase64<3> = serde_json::from_str(r#""3q2+""#).unwrap();
+        assert_eq!(value, OnlyBase64([0xde, 0xad, 0xbe]));
+
+        let error = serde_json::from_str::<OnlyBase64<2>>(r#""00ff""#).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "invalid value: invalid padding from index 3 to 3, expected bytes or an array of length 2 or a base64 string of length 4 (3 without padding) at line 1 column 6"
+        );
+        let error = serde_json::from_str::<OnlyBase64<3>>(r#""deadbe""#).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "invalid length 6, expected bytes or an array of length 3 or a base64 string of length 4 at line 1 column 8"
+        );
+    }
+
+    #[test]
+    fn auto_ambiguous_length() {
+        let value: Hex<2> = serde_json::from_str(r#""00ff""#).unwrap();
+        assert_eq!(value, Hex([0x00, 0xff]));
+        let value: Hex<2> = serde_json::from_str(r#""AP8=""#).unwrap();
+        assert_eq!(value, Hex([0x00, 0xff]));
+    }
+
+    #[test]
+    fn empty() {
+        let value: Hex<0> = serde_json::from_str(r#""""#).unwrap();
+        assert_eq!(value, Hex([]));
+        let error = serde_json::from_str::<Hex<0>>(r#""00""#).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "invalid length 2, expected bytes of length 0, an empty array, or an empty string at line 1 column 4"
+        );
+    }
+
+    #[test]
+    fn base64_url_safe() {
+        let json = serde_json::to_string(&Base64Url([0xfb, 0xff, 0xbf, 0xfe])).unwrap();
+        assert_eq!(json, r#""-_-__g==""#);
+        let value: Base64Url<4> = serde_json::from_str(&json).unwrap();
+        assert_eq!(value, Base64Url([0xfb, 0xff, 0xbf, 0xfe]));
+
+        // auto detects the alphabet
+        let value: Hex<3> = serde_json::from_str(r#""-_-_""#).unwrap();
+        assert_eq!(value, Hex([0xfb, 0xff, 0xbf]));
+        let value: Hex<3> = serde_json::from_str(r#""+/+/""#).unwrap();
+        assert_eq!(value, Hex([0xfb, 0xff, 0xbf]));
+        let value: Hex<4> = serde_json::from_str(r#""-_-__g==""#).unwrap();
+        assert_eq!(value, Hex([0xfb, 0xff, 0xbf, 0xfe]));
+
+        // but the alphabets cannot be mixed
+        let error = serde_json::from_str::<Hex<3>>(r#""-/-/""#).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "invalid value: invalid character `/` at index 1, expected bytes or an array of length 3 or a hex string of length 6 or a base64 or base64url string of length 4 at line 1 column 6"
+        );
+
+        // the pinned encodings only accept their own alphabet
+        let error = serde_json::from_str::<OnlyBase64<3>>(r#""-_-_""#).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "invalid value: invalid character `-` at index 0, expected bytes or an array of length 3 or a base64 string of length 4 at line 1 column 6"
+        );
+        let error = serde_json::from_str::<Base64Url<3>>(r#""+/+/""#).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "invalid value: invalid character `+` at index 0, expected bytes or an array of length 3 or a base64url string of length 4 at line 1 column 6"
+        );
+    }
+
+    #[test]
+    fn base64_unpadded() {
+        let value: OnlyBase64<4> = serde_json::from_str(r#""3q2+7w""#).unwrap();
+        assert_eq!(value, OnlyBase64([0xde, 0xad, 0xbe, 0xef]));
+        let value: Base64Url<4> = serde_json::from_str(r#""-_-__g""#).unwrap();
+        assert_eq!(value, Base64Url([0xfb, 0xff, 0xbf, 0xfe]));
+        let value: OnlyBase64<1> = serde_json::from_str(r#""/w""#).unwrap();
+        assert_eq!(value, OnlyBase64([0xff]));
+
+        let value: Hex<2> = serde_json::from_str(r#""AP8""#).unwrap();
+        assert_eq!(value, Hex([0x00, 0xff]));
+        let value: Hex<4> = serde_json::from_str(r#""3q2+7w""#).unwrap();
+        assert_eq!(value, Hex([0xde, 0xad, 0xbe, 0xef]));
+        let value: Hex<5> = serde_json::from_str(r#""3q2+7wA""#).unwrap();
+        assert_eq!(value, Hex([0xde, 0xad, 0xbe, 0xef, 0x00]));
+
+        // unpadded base64 and hex have the same length for N = 1
+        let value: Hex<1> = serde_json::from_str(r#""10""#).unwrap();
+        assert_eq!(value, Hex([0x10]));
+        let error = serde_json::from_str::<Hex<1>>(r#""/w""#).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "invalid value: invalid character `/` at index 0, expected bytes or an array of length 1 or a hex string of length 2 or a base64 or base64url string of length 4 (unpadded base64 is read as hex) at line 1 column 4"
+        );
+
+        let error = serde_json::from_str::<Hex<5>>(r#""3q2+7w""#).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "invalid length 6, expected bytes or an array of length 5 or a hex string of length 10 or a base64 or base64url string of length 8 (7 without padding) at line 1 column 8"
+        );
+        let error = serde_json::from_str::<Hex<2>>(r#""AP""#).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "invalid length 2, expected bytes or an array of length 2 or a hex, base64 or base64url string of length 4 or an unpadded base64 or base64url string of length 3 at line 1 column 4"
+        );
+    }
+
+    #[cfg(feature = "alloc")]
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Bytes(#[serde(with = "crate::vec")] Vec<u8>);
+
+    #[cfg(feature = "alloc")]
+    #[derive(Debug, PartialEq, Deserialize)]
+    struct BoxedBytes(#[serde(with = "crate::vec")] Box<[u8]>);
+
+    #[cfg(feature = "alloc")]
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Base64Bytes(#[serde(with = "crate::vec::base64")] Vec<u8>);
+
+    #[cfg(feature = "alloc")]
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Base64UrlBytes(#[serde(with = "crate::vec::base64_url")] Vec<u8>);
+
+    #[cfg(feature = "alloc")]
+    #[derive(Debug, PartialEq, Deserialize)]
+    struct BoundedBytes(
+        #[serde(deserialize_with = "crate::vec::deserialize_bounded::<_, _, 2, 4>")] Vec<u8>,
+    );
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn variable_length() {
+        let json = serde_json::to_string(&Bytes(vec![0xde, 0xad, 0xbe, 0xef, 0x00])).unwrap();
+        assert_eq!(json, r#""deadbeef00""#);
+        let value: Bytes = serde_json::from_str(&json).unwrap();
+        assert_eq!(value, Bytes(vec![0xde, 0xad, 0xbe, 0xef, 0x00]));
+
+        let value: Bytes = serde_json::from_str(r#""""#).unwrap();
+        assert_eq!(value, Bytes(vec![]));
+        let value: Bytes = serde_json::from_str(r#"[1, 2, 3]"#).unwrap();
+        assert_eq!(value, Bytes(vec![1, 2, 3]));
+        let value: Bytes = serde_json::from_str(r#""ABCD""#).unwrap();
+        assert_eq!(value, Bytes(vec![0xab, 0xcd]));
+        let value: BoxedBytes = serde_json::from_str(r#""abcd""#).unwrap();
+        assert_eq!(value, BoxedBytes(Box::new([0xab, 0xcd])));
+
+        let error = serde_json::from_str::<Bytes>(r#""3q2+7w==""#).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "invalid value: invalid character `q` at index 1, expected bytes, an array or a hex string at line 1 column 10"
+        );
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn variable_length_base64() {
+        let json = serde_json::to_string(&Base64Bytes(vec![0xde, 0xad, 0xbe, 0xef])).unwrap();
+        assert_eq!(json, r#""3q2+7w==""#);
+        let value: Base64Bytes = serde_json::from_str(&json).unwrap();
+        assert_eq!(value, Base64Bytes(vec![0xde, 0xad, 0xbe, 0xef]));
+        let value: Base64Bytes = serde_json::from_str(r#""3q2+7w""#).unwrap();
+        assert_eq!(value, Base64Bytes(vec![0xde, 0xad, 0xbe, 0xef]));
+        // also valid hex, but the encoding is pinned
+        let value: Base64Bytes = serde_json::from_str(r#""AAAA""#).unwrap();
+        assert_eq!(value, Base64Bytes(vec![0, 0, 0]));
+        let value: Base64UrlBytes = serde_json::from_str(r#""-_-_""#).unwrap();
+        assert_eq!(value, Base64UrlBytes(vec![0xfb, 0xff, 0xbf]));
+        let json = serde_json::to_string(&value).unwrap();
+        assert_eq!(json, r#""-_-_""#);
+
+        let error = serde_json::from_str::<Base64Bytes>(r#""3q2+7w=""#).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "invalid value: invalid padding from index 6 to 6, expected bytes, an array or a base64 string at line 1 column 9"
+        );
+        let error = serde_json::from_str::<Base64Bytes>(r#""3q2-7w==""#).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "invalid value: invalid character `-` at index 3, expected bytes, an array or a base64 string at line 1 column 10"
+        );
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn variable_length_bounds() {
+        let value: BoundedBytes = serde_json::from_str(r#""abcd""#).unwrap();
+        assert_eq!(value, BoundedBytes(vec![0xab, 0xcd]));
+        let value: BoundedBytes = serde_json::from_str(r#"[1, 2, 3, 4]"#).unwrap();
+        assert_eq!(value, BoundedBytes(vec![1, 2, 3, 4]));
+
+        let error = serde_json::from_str::<BoundedBytes>(r#""ab""#).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "invalid length 1, expected bytes, an array or a hex string of 2 to 4 bytes at line 1 column 4"
+        );
+        let error = serde_json::from_str::<BoundedBytes>(r#"[1, 2, 3, 4, 5, 6]"#).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "invalid length 6, expected bytes, an array or a hex string of 2 to 4 bytes at line 1 column 18"
+        );
+    }
+
+    fn binary_round_trip<T>(value: &T)
+    where
+        T: Serialize + for<'de> Deserialize<'de> + PartialEq + std::fmt::Debug,
+    {
+        let bytes = bincode::serialize(value).unwrap();
+        assert_eq!(&bincode::deserialize::<T>(&bytes).unwrap(), value);
+
+        let mut bytes = Vec::new();
+        ciborium::into_writer(value, &mut bytes).unwrap();
+        assert_eq!(&ciborium::from_reader::<T, _>(&bytes[..]).unwrap(), value);
+
+        let bytes = rmp_serde::to_vec(value).unwrap();
+        assert_eq!(&rmp_serde::from_slice::<T>(&bytes).unwrap(), value);
+
+        let json = serde_json::to_string(value).unwrap();
+        assert_eq!(&serde_json::from_str::<T>(&json).unwrap(), value);
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn binary_formats() {
+        binary_round_trip(&Hex(bytes::<0>()));
+        binary_round_trip(&Hex(bytes::<1>()));
+        binary_round_trip(&Hex(bytes::<32>()));
+        binary_round_trip(&Base64(bytes::<4>()));
+        binary_round_trip(&Base64Url(bytes::<5>()));
+        binary_round_trip(&crate::ByteArray::new(bytes::<16>()));
+        binary_round_trip(&vec![crate::ByteArray::new(bytes::<2>()); 3]);
+        binary_round_trip(&Bytes(vec![]));
+        binary_round_trip(&Bytes(vec![1, 2, 3]));
+
+        // raw bytes are written as such
+        let bytes = bincode::serialize(&Hex([0xde, 0xad])).unwrap();
+        assert_eq!(bytes, [2, 0, 0, 0, 0, 0, 0, 0, 0xde, 0xad]);
+        let bytes = rmp_serde::to_vec(&Hex([0xde, 0xad])).unwrap();
+        assert_eq!(bytes, [0xc4, 2, 0xde, 0xad]);
+
+        // CBOR and MessagePack are self-describing, so arrays are still accepted
+        let mut bytes = Vec::new();
+        ciborium::into_writer(&[0xde_u8, 0xad], &mut bytes).unwrap();
+        let value: Hex<2> = ciborium::from_reader(&bytes[..]).unwrap();
+        assert_eq!(value, Hex([0xde, 0xad]));
+        let bytes = rmp_serde::to_vec(&[0xde_u8, 0xad]).unwrap();
+        let value: Hex<2> = rmp_serde::from_slice(&bytes).unwrap();
+        assert_eq!(value, Hex([0xde, 0xad]));
+
+        // an array claiming 2^62 elements fails without reserving memory for them
+        let bytes = [0x9b, 0x40, 0, 0, 0, 0, 0, 0, 0, 1, 2, 3];
+        assert!(ciborium::from_reader::<Bytes, _>(&bytes[..]).is_err());
+
+        let bytes = bincode::serialize(&Bytes(vec![1, 2, 3])).unwrap();
+        let error = bincode::deserialize::<Hex<2>>(&bytes).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "invalid length 3, expected bytes or an array of length 2 or a hex, base64 or base64url string of length 4 or an unpadded base64 or base64url string of length 3"
+        );
+    }
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Optional {
+        #[serde(default, with = "crate::option")]
+        id: Option<[u8; 2]>,
+    }
+
+    #[cfg(feature = "alloc")]
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Sequence {
+        #[serde(with = "crate::seq")]
+        ids: Vec<[u8; 2]>,
+    }
+
+    #[test]
+    fn option() {
+        let json = serde_json::to_string(&Optional {
+            id: Some([0xde, 0xad]),
+        })
+        .unwrap();
+        assert_eq!(json, r#"{"id":"dead"}"#);
+        let json = serde_json::to_string(&Optional { id: None }).unwrap();
+        assert_eq!(json, r#"{"id":null}"#);
+
+        let value: Optional = serde_json::from_str(r#"{"id":"AP8="}"#).unwrap();
+        assert_eq!(
+            value,
+            Optional {
+                id: Some([0x00, 0xff])
+            }
+        );
+        let value: Optional = serde_json::from_str(r#"{"id":null}"#).unwrap();
+        assert_eq!(value, Optional { id: None });
+        let value: Optional = serde_json::from_str(r#"{}"#).unwrap();
+        assert_eq!(value, Optional { id: None });
+
+        let error = serde_json::from_str::<Optional>(r#"{"id":"ab!"}"#).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "invalid value: invalid character `!` at index 2, expected bytes or an array of length 2 or a hex, base64 or base64url string of length 4 or an unpadded base64 or base64url string of length 3 at line 1 column 11"
+        );
+
+        binary_round_trip(&Optional { id: Some([1, 2]) });
+        binary_round_trip(&Optional { id: None });
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn seq() {
+        let value = Sequence {
+            ids: vec![[0xde, 0xad], [0xbe, 0xef]],
+        };
+        let json = serde_json::to_string(&value).unwrap();
+        assert_eq!(json, r#"{"ids":["dead","beef"]}"#);
+        let value: Sequence = serde_json::from_str(r#"{"ids":["dead","vu8=",[0, 1]]}"#).unwrap();
+        assert_eq!(
+            value,
+            Sequence {
+                ids: vec![[0xde, 0xad], [0xbe, 0xef], [0, 1]]
+            }
+        );
+
+        binary_round_trip(&value);
+        binary_round_trip(&Sequence { ids: vec![] });
+    }
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct HexPrefixed<const N: usize>(#[serde(with = "crate::hex_prefixed")] [u8; N]);
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Base32<const N: usize>(#[serde(with = "crate::base32")] [u8; N]);
+
+    #[test]
+    fn hex_prefixed() {
+        let json = serde_json::to_string(&HexPrefixed([0xde, 0xad, 0xbe, 0xef])).unwrap();
+        assert_eq!(json, r#""0xdeadbeef""#);
+        let value: HexPrefixed<4> = serde_json::from_str(&json).unwrap();
+        assert_eq!(value, HexPrefixed([0xde, 0xad, 0xbe, 0xef]));
+        let value: HexPrefixed<4> = serde_json::from_str(r#""0XDEADBEEF""#).unwrap();
+        assert_eq!(value, HexPrefixed([0xde, 0xad, 0xbe, 0xef]));
+
+        let error = serde_json::from_str::<HexPrefixed<4>>(r#""0xdeadbeeg""#).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "invalid value: invalid character `g` at index 9, expected bytes or an array of length 4 or a 0x-prefixed hex string of length 10 at line 1 column 12"
+        );
+        let error = serde_json::from_str::<HexPrefixed<4>>(r#""00deadbeef""#).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "invalid value: missing `0x` prefix, expected bytes or an array of length 4 or a 0x-prefixed hex string of length 10 at line 1 column 12"
+        );
+        let error = serde_json::from_str::<HexPrefixed<4>>(r#""deadbeef""#).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "invalid length 8, expected bytes or an array of length 4 or a 0x-prefixed hex string of length 10 at line 1 column 10"
+        );
+    }
+
+    #[test]
+    fn base32() {
+        let json = serde_json::to_string(&Base32([0xde, 0xad, 0xbe, 0xef])).unwrap();
+        assert_eq!(json, r#""VTPVXVR""#);
+        let value: Base32<4> = serde_json::from_str(&json).unwrap();
+        assert_eq!(value, Base32([0xde, 0xad, 0xbe, 0xef]));
+        let value: Base32<4> = serde_json::from_str(r#""vtpvxvr""#).unwrap();
+        assert_eq!(value, Base32([0xde, 0xad, 0xbe, 0xef]));
+
+        let error = serde_json::from_str::<Base32<4>>(r#""VTPVUVR""#).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "invalid value: invalid character `U` at index 4, expected bytes or an array of length 4 or a Crockford base32 string of length 7 at line 1 column 9"
+        );
+        let error = serde_json::from_str::<Base32<4>>(r#""VTPVXVZ""#).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "invalid value: invalid last symbol `Z` at index 6, expected bytes or an array of length 4 or a Crockford base32 string of length 7 at line 1 column 9"
+        );
+        binary_round_trip(&Base32(bytes::<20>()));
+    }
+
+    #[cfg(feature = "base58")]
+    #[test]
+    fn base58() {
+        #[derive(Debug, PartialEq, Serialize, Deserialize)]
+        struct Base58<const N: usize>(#[serde(with = "crate::base58")] [u8; N]);
+
+        let json = serde_json::to_string(&Base58([0xde, 0xad, 0xbe, 0xef])).unwrap();
+        assert_eq!(json, r#""6h8cQN""#);
+        let value: Base58<4> = serde_json::from_str(&json).unwrap();
+        assert_eq!(value, Base58([0xde, 0xad, 0xbe, 0xef]));
+        let value: Base58<4> = serde_json::from_str(r#""1111""#).unwrap();
+        assert_eq!(value, Base58([0, 0, 0, 0]));
+
+        let error = serde_json::from_str::<Base58<4>>(r#""6h0cQN""#).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "invalid value: invalid character `0` at index 2, expected bytes or an array of length 4 or a base58 string at line 1 column 8"
+        );
+        let error = serde_json::from_str::<Base58<4>>(r#""111""#).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "invalid length 3, expected bytes or an array of length 4 or a base58 string at line 1 column 5"
+        );
+        let error = serde_json::from_str::<Base58<4>>(r#""6h8cQNN""#).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "invalid length 5, expected bytes or an array of length 4 or a base58 string at line 1 column 9"
+        );
+        binary_round_trip(&Base58(bytes::<32>()));
+    }
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Integers {
+        #[serde(with = "crate::be")]
+        be: u32,
+        #[serde(with = "crate::le")]
+        le: i16,
+    }
+
+    #[test]
+    fn integers() {
+        let value = Integers {
+            be: 0xdeadbeef,
+            le: -2,
+        };
+        let json = serde_json::to_string(&value).unwrap();
+        assert_eq!(json, r#"{"be":"deadbeef","le":"feff"}"#);
+        let value: Integers = serde_json::from_str(r#"{"be":"3q2+7w","le":[1, 0]}"#).unwrap();
+        assert_eq!(
+            value,
+            Integers {
+                be: 0xdeadbeef,
+                le: 1
+            }
+        );
+
+        let error = serde_json::from_str::<Integers>(r#"{"be":"dead","le":"0100"}"#).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "invalid length 4, expected bytes or an array of length 4 or a hex, base64 or base64url string of length 8 or an unpadded base64 or base64url string of length 6 at line 1 column 12"
+        );
+        binary_round_trip(&value);
+    }
+
+    #[cfg(feature = "uuid")]
+    #[test]
+    fn uuid() {
+        #[derive(Debug, PartialEq, Serialize, Deserialize)]
+        struct Uuid(#[serde(with = "crate::uuid")] ::uuid::Uuid);
+
+        let uuid = ::uuid::Uuid::from_u128(0x67e55044_10b1_426f_9247_bb680e5fe0c8);
+        let json = serde_json::to_string(&Uuid(uuid)).unwrap();
+        assert_eq!(json, r#""67e5504410b1426f9247bb680e5fe0c8""#);
+        let value: Uuid = serde_json::from_str(r#""Z+VQRBCxQm+SR7toDl/gyA""#).unwrap();
+        assert_eq!(value, Uuid(uuid));
+
+        let error =
+            serde_json::from_str::<Uuid>(r#""67e55044-10b1-426f-9247-bb680e5fe0c8""#).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "invalid length 36, expected bytes or an array of length 16 or a hex string of length 32 or a base64 or base64url string of length 24 (22 without padding) at line 1 column 38"
+        );
+        binary_round_trip(&Uuid(uuid));
+    }
+
+    #[cfg(feature = "generic-array")]
+    #[test]
+    fn generic_array() {
+        use ::generic_array::{typenum::U4, GenericArray};
+
+        #[derive(Debug, PartialEq, Serialize, Deserialize)]
+        struct Generic(#[serde(with = "crate::generic_array")] GenericArray<u8, U4>);
+
+        let bytes = GenericArray::from([0xde, 0xad, 0xbe, 0xef]);
+        let json = serde_json::to_string(&Generic(bytes)).unwrap();
+        assert_eq!(json, r#""deadbeef""#);
+        let value: Generic = serde_json::from_str(r#""3q2-7w==""#).unwrap();
+        assert_eq!(value, Generic(bytes));
+
+        let error = serde_json::from_str::<Generic>(r#"[1, 2, 3]"#).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "invalid length 3, expected bytes or an array of length 4 or a hex, base64 or base64url string of length 8 or an unpadded base64 or base64url string of length 6 at line 1 column 9"
+        );
+        binary_round_trip(&Generic(bytes));
+    }
+
+    #[cfg(feature = "heapless")]
+    #[test]
+    fn heapless() {
+        #[derive(Debug, PartialEq, Serialize, Deserialize)]
+        struct Heapless(#[serde(with = "crate::heapless")] ::heapless::Vec<u8, 4>);
+
+        let bytes = ::heapless::Vec::from_slice(&[0xde, 0xad, 0xbe]).unwrap();
+        let json = serde_json::to_string(&Heapless(bytes.clone())).unwrap();
+        assert_eq!(json, r#""deadbe""#);
+        let value: Heapless = serde_json::from_str(&json).unwrap();
+        assert_eq!(value, Heapless(bytes.clone()));
+        let value: Heapless = serde_json::from_str(r#"[222, 173, 190]"#).unwrap();
+        assert_eq!(value, Heapless(bytes.clone()));
+
+        let error = serde_json::from_str::<Heapless>(r#""deadbeef00""#).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "invalid length 5, expected bytes, an array or a hex string of at most 4 bytes at line 1 column 12"
+        );
+        let error = serde_json::from_str::<Heapless>(r#"[1, 2, 3, 4, 5]"#).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "invalid length 5, expected bytes, an array or a hex string of at most 4 bytes at line 1 column 15"
+        );
+        binary_round_trip(&Heapless(bytes));
+    }
+
+    #[test]
+    fn decode() {
+        use crate::{ByteArrayError, StringEncoding};
+
+        assert_eq!(crate::decode::<4>("deadbeef"), Ok([0xde, 0xad, 0xbe, 0xef]));
+        assert_eq!(crate::decode::<4>("3q2-7w"), Ok([0xde, 0xad, 0xbe, 0xef]));
+        assert_eq!(crate::decode::<0>(""), Ok([]));
+        assert_eq!(
+            crate::decode::<4>("deadbeef00"),
+            Err(ByteArrayError::InvalidLength {
+                expected: 4,
+                actual: 10
+            })
+        );
+        assert_eq!(
+            crate::decode::<4>("deadbeeg"),
+            Err(ByteArrayError::InvalidCharacter {
+                c: 'g',
+                index: 7,
+                encoding: StringEncoding::Hex
+            })
+        );
+        assert_eq!(
+            crate::decode::<4>("3q2_7!"),
+            Err(ByteArrayError::InvalidCharacter {
+                c: '!',
+                index: 5,
+                encoding: StringEncoding::Base64Url
+            })
+        );
+        assert_eq!(
+            crate::decode::<4>("3q2+7x"),
+            Err(ByteArrayError::InvalidLastSymbol {
+                c: 'x',
+                index: 5,
+                encoding: StringEncoding::Base64
+            })
+        );
+        assert_eq!(
+            crate::decode::<5>("3q2+7wAA"),
+            Err(ByteArrayError::InvalidPadding { from: 7, to: 7 })
+        );
+
+        let error = crate::decode::<4>("3q2+7x").unwrap_err();
+        assert_eq!(error.to_string(), "invalid last symbol `x` at index 5");
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn lenient() {
+        use crate::{ByteArrayError, StringEncoding};
+
+        #[derive(Debug, PartialEq, Serialize, Deserialize)]
+        struct Lenient<const N: usize>(#[serde(with = "crate::lenient")] [u8; N]);
+
+        for input in [
+            "deadbeef",
+            "DEADBEEF",
+            "DeAdBeEf",
+            "de:ad:be:ef",
+            "DE:AD:BE:EF",
+            "de ad be ef",
+            " deadbeef\n",
+            "3q2+7w==",
+            "3q2+\r\n7w==",
+            "3q2-\n7w",
+        ] {
+            let json = serde_json::to_string(input).unwrap();
+            let value: Lenient<4> = serde_json::from_str(&json).unwrap();
+            assert_eq!(value, Lenient([0xde, 0xad, 0xbe, 0xef]), "{:?}", input);
+        }
+
+        // mixed case hex is accepted in strict mode as well, separators are not
+        let value: Hex<4> = serde_json::from_str(r#""DeAdBeEf""#).unwrap();
+        assert_eq!(value, Hex([0xde, 0xad, 0xbe, 0xef]));
+        let error = serde_json::from_str::<Hex<4>>(r#""de:ad:be:ef""#).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "invalid length 11, expected bytes or an array of length 4 or a hex, base64 or base64url string of length 8 or an unpadded base64 or base64url string of length 6 at line 1 column 13"
+        );
+
+        let error = serde_json::from_str::<Lenient<4>>(r#""de:ad:be:ef:00""#).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "invalid length 10, expected bytes or an array of length 4 or a hex, base64 or base64url string of length 8 or an unpadded base64 or base64url string of length 6 (whitespace and `:` are ignored) at line 1 column 16"
+        );
+        assert_eq!(
+            crate::decode_lenient::<4>("de:ad:be:eg"),
+            Err(ByteArrayError::InvalidCharacter {
+                c: 'g',
+                index: 10,
+                encoding: StringEncoding::Hex
+            })
+        );
+        assert_eq!(
+            crate::decode_lenient::<5>("3q2+ 7w=A"),
+            Err(ByteArrayError::InvalidPadding { from: 8, to: 8 })
+        );
+
+        let json = serde_json::to_string(&Lenient([0xde, 0xad])).unwrap();
+        assert_eq!(json, r#""dead""#);
+    }
 }
@@ -0,0 +1,118 @@
+use crate::ByteArray;
+use ::rocket::form::{self, FromFormField, ValueField};
+use ::rocket::http::impl_from_uri_param_identity;
+use ::rocket::http::uri::fmt::{Formatter, Part, UriDisplay};
+use ::rocket::request::FromParam;
+use std::fmt;
+
+/// Parses a hex or base64 path segment, see [`crate::auto`].
+///
+/// Since `/` separates path segments, base64 should use the URL-safe alphabet.
+impl<'a, const N: usize> FromParam<'a> for ByteArray<N> {
+    type Error = serde::de::value::Error;
+
+    fn from_param(param: &'a str) -> Result<Self, Self::Error> {
+        param.parse()
+    }
+}
+
+/// Parses a hex or base64 form value, see [`crate::auto`].
+impl<'v, const N: usize> FromFormField<'v> for ByteArray<N> {
+    fn from_value(field: ValueField<'v>) -> form::Result<'v, Self> {
+        field
+            .value
+            .parse()
+            .map_err(|e: serde::de::value::Error| form::Error::validation(e.to_string()).into())
+    }
+}
+
+/// Formats the bytes as a lowercase hex string.
+impl<P: Part, const N: usize> UriDisplay<P> for ByteArray<N> {
+    fn fmt(&self, f: &mut Formatter<'_, P>) -> fmt::Result {
+        f.write_value(self.to_string())
+    }
+}
+
+impl_from_uri_param_identity!((const N: usize) ByteArray<N>);
+
+#[cfg(test)]
+mod tests {
+    use crate::ByteArray;
+    use rocket::form::Form;
+    use rocket::http::{ContentType, Status};
+    use rocket::local::blocking::Client;
+    use rocket::request::FromParam;
+    use rocket::{get, post, routes, uri, FromForm};
+
+    #[get("/objects/<id>?<version>")]
+    fn object(id: ByteArray<4>, version: Option<ByteArray<2>>) -> String {
+        match version {
+            Some(version) => format!("{id} {version}"),
+            None => id.to_string(),
+        }
+    }
+
+    #[derive(FromForm)]
+    struct Object {
+        id: ByteArray<4>,
+    }
+
+    #[post("/objects", data = "<object>")]
+    fn create(object: Form<Object>) -> String {
+        object.id.to_string()
+    }
+
+    #[test]
+    fn uri() {
+        let id = ByteArray::new([0xde, 0xad, 0xbe, 0xef]);
+        assert_eq!(uri!(object(id, _)).to_string(), "/objects/deadbeef");
+        assert_eq!(
+            uri!(object(&id, Some(ByteArray::new([0, 1])))).to_string(),
+            "/objects/deadbeef?version=0001"
+        );
+    }
+
+    #[test]
+    fn request() {
+        let rocket = rocket::build().mount("/", routes![object, create]);
+        let client = Client::tracked(rocket).unwrap();
+
+        let response = client.get("/objects/deadbeef").dispatch();
+        assert_eq!(response.status(), Status::Ok);
+        assert_eq!(response.into_string().unwrap(), "deadbeef");
+
+        let response = client.get("/objects/-_-__g?version=AAE").dispatch();
+        assert_eq!(response.status(), Status::Ok);
+        assert_eq!(response.into_string().unwrap(), "fbffbffe 0001");
+
+        let response = client.get("/objects/dead").dispatch();
+        assert_eq!(response.status(), Status::UnprocessableEntity);
+
+        let response = client
+            .post("/objects")
+            .header(ContentType::Form)
+            .body("id=3q2%2B7w%3D%3D")
+            .dispatch();
+        assert_eq!(response.status(), Status::Ok);
+        assert_eq!(response.into_string().unwrap(), "deadbeef");
+
+        let response = client
+            .post("/objects")
+            .header(ContentType::Form)
+            .body("id=dead")
+            .dispatch();
+        assert_eq!(response.status(), Status::UnprocessableEntity);
+    }
+
+    #[test]
+    fn errors() {
+        // the same messages as deserializing
+        let message = "invalid length 4, expected bytes or an array of length 4 or a hex, base64 or base64url string of length 8 or an unpadded base64 or base64url string of length 6";
+        let error = <ByteArray<4> as FromParam>::from_param("dead").unwrap_err();
+        assert_eq!(error.to_string(), message);
+
+        let errors = Form::<Object>::parse("id=dead").err().unwrap();
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].kind.to_string(), message);
+    }
+}
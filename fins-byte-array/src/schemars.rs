@@ -0,0 +1,146 @@
+use crate::visitor::{base64_padded_size, base64_unpadded_size, hex_size};
+use crate::ByteArray;
+use ::schemars::{json_schema, JsonSchema, Schema, SchemaGenerator};
+use std::borrow::Cow;
+
+fn base64_pattern<const N: usize>() -> String {
+    let padding = base64_padded_size(N) - base64_unpadded_size(N);
+    match (N, padding) {
+        (_, 0) => format!("^[A-Za-z0-9+/_-]{{{}}}$", base64_unpadded_size(N)),
+        // unpadded base64 is read as hex
+        (1, _) => format!(
+            "^[A-Za-z0-9+/_-]{{{}}}={{{}}}$",
+            base64_unpadded_size(N),
+            padding
+        ),
+        _ => format!(
+            "^[A-Za-z0-9+/_-]{{{}}}(={{{}}})?$",
+            base64_unpadded_size(N),
+            padding
+        ),
+    }
+}
+
+/// Describes the inputs accepted by [`crate::auto`]: a hex string, a base64 string or an array of bytes.
+impl<const N: usize> JsonSchema for ByteArray<N> {
+    fn schema_name() -> Cow<'static, str> {
+        format!("ByteArray{}", N).into()
+    }
+
+    fn schema_id() -> Cow<'static, str> {
+        format!("{}::ByteArray<{}>", module_path!(), N).into()
+    }
+
+    fn json_schema(_generator: &mut SchemaGenerator) -> Schema {
+        let array = json_schema!({
+            "type": "array",
+            "items": {
+                "type": "integer",
+                "format": "uint8",
+                "minimum": 0,
+                "maximum": 255,
+            },
+            "minItems": N,
+            "maxItems": N,
+        });
+        if N == 0 {
+            return json_schema!({
+                "oneOf": [
+                    {
+                        "type": "string",
+                        "maxLength": 0,
+                    },
+                    array,
+                ],
+            });
+        }
+        json_schema!({
+            "oneOf": [
+                {
+                    "description": "hex string",
+                    "type": "string",
+                    "minLength": hex_size(N),
+                    "maxLength": hex_size(N),
+                    "pattern": format!("^[0-9A-Fa-f]{{{}}}$", hex_size(N)),
+                },
+                {
+                    "description": "base64 or base64url string",
+                    "type": "string",
+                    "minLength": if N == 1 { base64_padded_size(N) } else { base64_unpadded_size(N) },
+                    "maxLength": base64_padded_size(N),
+                    "pattern": base64_pattern::<N>(),
+                },
+                array,
+            ],
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ByteArray;
+    use schemars::{schema_for, JsonSchema};
+    use serde_json::json;
+
+    #[derive(JsonSchema)]
+    #[allow(dead_code)]
+    struct Object {
+        id: ByteArray<4>,
+    }
+
+    #[test]
+    fn schema() {
+        let schema = schema_for!(ByteArray<4>);
+        assert_eq!(
+            schema.as_value(),
+            &json!({
+                "$schema": "https://json-schema.org/draft/2020-12/schema",
+                "title": "ByteArray4",
+                "oneOf": [
+                    {
+                        "description": "hex string",
+                        "type": "string",
+                        "minLength": 8,
+                        "maxLength": 8,
+                        "pattern": "^[0-9A-Fa-f]{8}$",
+                    },
+                    {
+                        "description": "base64 or base64url string",
+                        "type": "string",
+                        "minLength": 6,
+                        "maxLength": 8,
+                        "pattern": "^[A-Za-z0-9+/_-]{6}(={2})?$",
+                    },
+                    {
+                        "type": "array",
+                        "items": {
+                            "type": "integer",
+                            "format": "uint8",
+                            "minimum": 0,
+                            "maximum": 255,
+                        },
+                        "minItems": 4,
+                        "maxItems": 4,
+                    },
+                ],
+            })
+        );
+    }
+
+    #[test]
+    fn patterns() {
+        assert_eq!(super::base64_pattern::<1>(), "^[A-Za-z0-9+/_-]{2}={2}$");
+        assert_eq!(super::base64_pattern::<2>(), "^[A-Za-z0-9+/_-]{3}(={1})?$");
+        assert_eq!(super::base64_pattern::<3>(), "^[A-Za-z0-9+/_-]{4}$");
+    }
+
+    #[test]
+    fn field() {
+        let schema = schema_for!(Object);
+        assert_eq!(
+            schema.as_value()["properties"]["id"],
+            json!({ "$ref": "#/$defs/ByteArray4" })
+        );
+        assert!(schema.as_value()["$defs"]["ByteArray4"]["oneOf"].is_array());
+    }
+}
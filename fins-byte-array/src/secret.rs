@@ -0,0 +1,201 @@
+use crate::visitor::{ByteArrayVisitor, Encoding};
+use core::fmt::{Debug, Display};
+use serde::de::{Error, SeqAccess, Visitor};
+use serde::{Deserialize, Deserializer};
+use subtle::{Choice, ConstantTimeEq};
+use zeroize::{Zeroize, ZeroizeOnDrop, Zeroizing};
+
+/// A fixed number of secret bytes that deserializes like [`crate::deserialize`].
+///
+/// Compares in constant time, is zeroized on drop and redacts its content in `Debug` and `Display`.
+/// It intentionally does not implement `Serialize`.
+#[derive(Clone)]
+pub struct SecretByteArray<const N: usize>([u8; N]);
+
+impl<const N: usize> SecretByteArray<N> {
+    pub const fn new(bytes: [u8; N]) -> Self {
+        Self(bytes)
+    }
+
+    pub fn expose_secret(&self) -> &[u8; N] {
+        &self.0
+    }
+}
+
+impl<const N: usize> From<[u8; N]> for SecretByteArray<N> {
+    fn from(bytes: [u8; N]) -> Self {
+        Self(bytes)
+    }
+}
+
+impl<const N: usize> ConstantTimeEq for SecretByteArray<N> {
+    fn ct_eq(&self, other: &Self) -> Choice {
+        self.0.ct_eq(&other.0)
+    }
+}
+
+impl<const N: usize> PartialEq for SecretByteArray<N> {
+    fn eq(&self, other: &Self) -> bool {
+        self.ct_eq(other).into()
+    }
+}
+
+impl<const N: usize> Eq for SecretByteArray<N> {}
+
+impl<const N: usize> Zeroize for SecretByteArray<N> {
+    fn zeroize(&mut self) {
+        self.0.zeroize();
+    }
+}
+
+impl<const N: usize> Drop for SecretByteArray<N> {
+    fn drop(&mut self) {
+        self.zeroize();
+    }
+}
+
+impl<const N: usize> ZeroizeOnDrop for SecretByteArray<N> {}
+
+impl<const N: usize> Debug for SecretByteArray<N> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "SecretByteArray<{}>([REDACTED])", N)
+    }
+}
+
+impl<const N: usize> Display for SecretByteArray<N> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str("[REDACTED]")
+    }
+}
+
+impl<'de, const N: usize> Deserialize<'de> for SecretByteArray<N> {
+    /// Deserializes like [`crate::deserialize`], but errors never include the content.
+    ///
+    /// The bytes are decoded into a buffer that is zeroized on drop. Zeroizing is best-effort:
+    /// the serialized input is owned by the deserializer, and moves may leave copies on the stack.
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let bytes = crate::deserialize_fixed(deserializer, SecretVisitor::<N>)?;
+        Ok(Self(*bytes))
+    }
+}
+
+/// Wraps a [`ByteArrayVisitor`] and replaces its errors, which may contain parts of the secret.
+struct SecretVisitor<const N: usize>;
+
+impl<const N: usize> SecretVisitor<N> {
+    fn visitor() -> ByteArrayVisitor<Zeroizing<[u8; N]>> {
+        ByteArrayVisitor::new(Encoding::Auto)
+    }
+
+    fn invalid<E: Error>() -> E {
+        E::custom(format_args!("invalid secret of {N} bytes"))
+    }
+}
+
+impl<'de, const N: usize> Visitor<'de> for SecretVisitor<N> {
+    type Value = Zeroizing<[u8; N]>;
+
+    fn expecting(&self, formatter: &mut core::fmt::Formatter) -> core::fmt::Result {
+        write!(formatter, "a secret of {N} bytes")
+    }
+
+    fn visit_bool<E: Error>(self, _v: bool) -> Result<Self::Value, E> {
+        Err(Self::invalid())
+    }
+
+    fn visit_i64<E: Error>(self, _v: i64) -> Result<Self::Value, E> {
+        Err(Self::invalid())
+    }
+
+    fn visit_i128<E: Error>(self, _v: i128) -> Result<Self::Value, E> {
+        Err(Self::invalid())
+    }
+
+    fn visit_u64<E: Error>(self, _v: u64) -> Result<Self::Value, E> {
+        Err(Self::invalid())
+    }
+
+    fn visit_u128<E: Error>(self, _v: u128) -> Result<Self::Value, E> {
+        Err(Self::invalid())
+    }
+
+    fn visit_f64<E: Error>(self, _v: f64) -> Result<Self::Value, E> {
+        Err(Self::invalid())
+    }
+
+    fn visit_str<E: Error>(self, v: &str) -> Result<Self::Value, E> {
+        Self::visitor().visit_str(v).map_err(|_: E| Self::invalid())
+    }
+
+    fn visit_bytes<E: Error>(self, v: &[u8]) -> Result<Self::Value, E> {
+        Self::visitor()
+            .visit_bytes(v)
+            .map_err(|_: E| Self::invalid())
+    }
+
+    fn visit_seq<A>(self, seq: A) -> Result<Self::Value, A::Error>
+    where
+        A: SeqAccess<'de>,
+    {
+        Self::visitor().visit_seq(seq).map_err(|_| Self::invalid())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SecretByteArray;
+    use zeroize::Zeroize;
+
+    #[test]
+    fn redacted() {
+        let secret = SecretByteArray::new([0xde, 0xad, 0xbe, 0xef]);
+        assert_eq!(format!("{:?}", secret), "SecretByteArray<4>([REDACTED])");
+        assert_eq!(secret.to_string(), "[REDACTED]");
+    }
+
+    #[test]
+    fn eq() {
+        let secret = SecretByteArray::new([0xde, 0xad, 0xbe, 0xef]);
+        assert_eq!(secret, SecretByteArray::new([0xde, 0xad, 0xbe, 0xef]));
+        assert_ne!(secret, SecretByteArray::new([0xde, 0xad, 0xbe, 0xee]));
+    }
+
+    #[test]
+    fn zeroize() {
+        let mut secret = SecretByteArray::new([0xde, 0xad, 0xbe, 0xef]);
+        secret.zeroize();
+        assert_eq!(secret.expose_secret(), &[0; 4]);
+    }
+
+    #[test]
+    fn deserialize() {
+        let secret: SecretByteArray<4> = serde_json::from_str(r#""3q2+7w==""#).unwrap();
+        assert_eq!(secret.expose_secret(), &[0xde, 0xad, 0xbe, 0xef]);
+
+        let error = serde_json::from_str::<SecretByteArray<4>>(r#""deadbeef00""#).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "invalid secret of 4 bytes at line 1 column 12"
+        );
+    }
+
+    #[test]
+    fn redacted_errors() {
+        for json in [
+            r#""deadbeeg""#,
+            r#""3q2+7w=!""#,
+            "[222, 173, 190, 999]",
+            "[222, 173]",
+            "1234",
+        ] {
+            let error = serde_json::from_str::<SecretByteArray<4>>(json).unwrap_err();
+            let error = error.to_string();
+            assert!(error.starts_with("invalid secret of 4 bytes"), "{error}");
+        }
+        let secret: SecretByteArray<4> = serde_json::from_str("[222, 173, 190, 239]").unwrap();
+        assert_eq!(secret.expose_secret(), &[0xde, 0xad, 0xbe, 0xef]);
+    }
+}
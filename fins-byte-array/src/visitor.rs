@@ -0,0 +1,1276 @@
+use base64::{
+    engine::GeneralPurpose,
+    prelude::{BASE64_STANDARD_NO_PAD, BASE64_URL_SAFE_NO_PAD},
+    Engine as _,
+};
+use serde::de::{Error, Expected, SeqAccess, Unexpected, Visitor};
+
+use crate::crockford;
+use crate::{ByteArrayError, StringEncoding};
+#[cfg(feature = "alloc")]
+use alloc::{vec, vec::Vec};
+use core::fmt::{self, Write as _};
+use core::marker::PhantomData;
+
+/// The string encodings accepted by a [`ByteArrayVisitor`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Encoding {
+    /// Hex or base64, depending on the length of the string.
+    /// Base64 may use the standard or the URL-safe alphabet.
+    Auto,
+    Hex,
+    /// Hex with a `0x` or `0X` prefix.
+    HexPrefixed,
+    Base64,
+    Base64Url,
+    /// Crockford base32.
+    Base32,
+    #[cfg(feature = "base58")]
+    Base58,
+}
+
+/// The output of a [`ByteArrayVisitor`], exactly `LEN` bytes.
+pub(crate) trait FixedBytes: AsMut<[u8]> + Sized {
+    const LEN: usize;
+
+    fn zeroed() -> Self;
+}
+
+impl<const N: usize> FixedBytes for [u8; N] {
+    const LEN: usize = N;
+
+    fn zeroed() -> Self {
+        [0; N]
+    }
+}
+
+/// Zeroized on drop, also when decoding fails halfway.
+#[cfg(feature = "secret")]
+impl<const N: usize> FixedBytes for zeroize::Zeroizing<[u8; N]> {
+    const LEN: usize = N;
+
+    fn zeroed() -> Self {
+        zeroize::Zeroizing::new([0; N])
+    }
+}
+
+#[cfg(feature = "generic-array")]
+impl<L: generic_array::ArrayLength> FixedBytes for generic_array::GenericArray<u8, L> {
+    const LEN: usize = L::USIZE;
+
+    fn zeroed() -> Self {
+        Self::default()
+    }
+}
+
+pub(crate) struct ByteArrayVisitor<B> {
+    encoding: Encoding,
+    /// Ignore whitespace and separators in strings, see [`is_separator`].
+    #[cfg(feature = "alloc")]
+    lenient: bool,
+    output: PhantomData<B>,
+}
+
+impl<B: FixedBytes> ByteArrayVisitor<B> {
+    pub(crate) fn new(encoding: Encoding) -> Self {
+        Self {
+            encoding,
+            #[cfg(feature = "alloc")]
+            lenient: false,
+            output: PhantomData,
+        }
+    }
+
+    #[cfg(feature = "alloc")]
+    pub(crate) fn lenient(self) -> Self {
+        Self {
+            lenient: true,
+            ..self
+        }
+    }
+
+    #[cfg(feature = "alloc")]
+    fn is_lenient(&self) -> bool {
+        self.lenient
+    }
+
+    #[cfg(not(feature = "alloc"))]
+    fn is_lenient(&self) -> bool {
+        false
+    }
+}
+
+/// Characters removed in lenient mode.
+///
+/// `-` and `_` are not separators since they are part of the base64url alphabet.
+#[cfg(feature = "alloc")]
+fn is_separator(c: char) -> bool {
+    c.is_whitespace() || c == ':'
+}
+
+/// Maps a byte index of `v` without separators back to a byte index of `v`.
+#[cfg(feature = "alloc")]
+fn original_index(v: &str, index: usize) -> usize {
+    let mut offset = 0;
+    for (i, c) in v.char_indices().filter(|&(_, c)| !is_separator(c)) {
+        if offset >= index {
+            return i;
+        }
+        offset += c.len_utf8();
+    }
+    v.len()
+}
+
+pub(crate) const fn hex_size(n: usize) -> usize {
+    n * 2
+}
+
+const fn div_ceil(dividend: usize, diviser: usize) -> usize {
+    dividend.div_ceil(diviser)
+}
+
+pub(crate) const fn base64_padded_size(n: usize) -> usize {
+    div_ceil(n, 3) * 4
+}
+
+pub(crate) const fn base64_unpadded_size(n: usize) -> usize {
+    div_ceil(n * 4, 3)
+}
+
+/*
+const fn base64_padding_size(n: usize) -> usize {
+    n * 2 % 3
+}
+*/
+
+fn base64_check_padding(v: &str, n: usize) -> bool {
+    v.as_bytes()[base64_unpadded_size(n)..base64_padded_size(n)]
+        .iter()
+        .all(|&c| c == b'=')
+}
+
+fn base64_is_url_safe(v: &str) -> bool {
+    v.bytes().any(|c| c == b'-' || c == b'_')
+}
+
+#[derive(Clone, Copy)]
+enum Decoding {
+    Hex,
+    HexPrefixed,
+    /// [`StringEncoding::Base64`] or [`StringEncoding::Base64Url`] and the length of the string without padding.
+    Base64(StringEncoding, usize),
+    Base32,
+    #[cfg(feature = "base58")]
+    Base58,
+}
+
+/// A fixed-capacity buffer to format error messages without allocating.
+struct MessageBuffer {
+    buffer: [u8; 128],
+    len: usize,
+}
+
+impl MessageBuffer {
+    fn new() -> Self {
+        Self {
+            buffer: [0; 128],
+            len: 0,
+        }
+    }
+
+    fn as_str(&self) -> &str {
+        // only whole `&str`s are written, so this is always valid UTF-8
+        core::str::from_utf8(&self.buffer[..self.len]).unwrap_or_default()
+    }
+}
+
+impl fmt::Write for MessageBuffer {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        let buffer = self
+            .buffer
+            .get_mut(self.len..self.len + s.len())
+            .ok_or(fmt::Error)?;
+        buffer.copy_from_slice(s.as_bytes());
+        self.len += s.len();
+        Ok(())
+    }
+}
+
+/// Maps a [`ByteArrayError`] to a serde error, length errors use [`Error::invalid_length`].
+fn to_serde<E: Error>(error: ByteArrayError, exp: &dyn Expected) -> E {
+    match error {
+        ByteArrayError::InvalidLength { actual, .. }
+        | ByteArrayError::TooManyElements { actual, .. } => Error::invalid_length(actual, exp),
+        error => {
+            let mut message = MessageBuffer::new();
+            // a message that does not fit is truncated
+            let _ = write!(message, "{}", error);
+            Error::invalid_value(Unexpected::Other(message.as_str()), exp)
+        }
+    }
+}
+
+/// Converts the error of decoding the base58 string `v` into `expected` bytes.
+#[cfg(feature = "base58")]
+fn base58_error(v: &str, expected: usize, error: bs58::decode::Error) -> ByteArrayError {
+    let invalid_character = |error| match error {
+        bs58::decode::Error::InvalidCharacter { character, index } => {
+            Some(ByteArrayError::InvalidCharacter {
+                c: character,
+                index,
+                encoding: StringEncoding::Base58,
+            })
+        }
+        bs58::decode::Error::NonAsciiCharacter { index } => {
+            let (index, c) = char_at(v, index);
+            Some(ByteArrayError::InvalidCharacter {
+                c,
+                index,
+                encoding: StringEncoding::Base58,
+            })
+        }
+        _ => None,
+    };
+    invalid_character(error).unwrap_or_else(|| {
+        // the bytes do not fit, decode again for their actual number, at most one per character
+        match bs58::decode(v).into_vec() {
+            Ok(bytes) => ByteArrayError::InvalidLength {
+                expected,
+                actual: bytes.len(),
+            },
+            // decoding into a `Vec` only fails for invalid characters
+            Err(error) => invalid_character(error).unwrap_or(ByteArrayError::InvalidLength {
+                expected,
+                actual: v.len(),
+            }),
+        }
+    })
+}
+
+/// Decodes the hex string `v[offset..]`, indices in errors are relative to `v`.
+fn decode_hex(
+    v: &str,
+    offset: usize,
+    data: &mut [u8],
+    encoding: StringEncoding,
+) -> Result<usize, ByteArrayError> {
+    hex::decode_to_slice(&v[offset..], data)
+        .map(|()| data.len())
+        .map_err(|e| match e {
+            hex::FromHexError::InvalidHexCharacter { index, .. } => {
+                // `hex` reports single bytes of multi-byte characters
+                let (index, c) = char_at(v, index + offset);
+                ByteArrayError::InvalidCharacter { c, index, encoding }
+            }
+            hex::FromHexError::OddLength | hex::FromHexError::InvalidStringLength => {
+                ByteArrayError::InvalidLength {
+                    expected: data.len(),
+                    actual: v.len(),
+                }
+            }
+        })
+}
+
+fn base64_engine(encoding: StringEncoding) -> &'static GeneralPurpose {
+    if encoding == StringEncoding::Base64Url {
+        &BASE64_URL_SAFE_NO_PAD
+    } else {
+        &BASE64_STANDARD_NO_PAD
+    }
+}
+
+/// The character of `v` that contains the byte at `index`, and the index where it starts.
+fn char_at(v: &str, index: usize) -> (usize, char) {
+    let index = (0..=index.min(v.len()))
+        .rev()
+        .find(|&i| v.is_char_boundary(i))
+        .unwrap_or(0);
+    (index, v[index..].chars().next().unwrap_or_default())
+}
+
+impl Decoding {
+    fn base64(v: &str, unpadded_len: usize) -> Self {
+        if base64_is_url_safe(v) {
+            Decoding::Base64(StringEncoding::Base64Url, unpadded_len)
+        } else {
+            Decoding::Base64(StringEncoding::Base64, unpadded_len)
+        }
+    }
+
+    fn encoding(&self) -> StringEncoding {
+        match self {
+            Decoding::Hex => StringEncoding::Hex,
+            Decoding::HexPrefixed => StringEncoding::HexPrefixed,
+            Decoding::Base64(encoding, _) => *encoding,
+            Decoding::Base32 => StringEncoding::Base32,
+            #[cfg(feature = "base58")]
+            Decoding::Base58 => StringEncoding::Base58,
+        }
+    }
+
+    /// Decodes `v` into `data` and returns the number of decoded bytes.
+    fn decode(self, v: &str, data: &mut [u8]) -> Result<usize, ByteArrayError> {
+        let encoding = self.encoding();
+        let invalid_length = ByteArrayError::InvalidLength {
+            expected: data.len(),
+            actual: v.len(),
+        };
+        match self {
+            Decoding::Base64(_, unpadded_len) => base64_engine(encoding)
+                .decode_slice(&v[..unpadded_len], data)
+                .map_err(|e| match e {
+                    base64::DecodeSliceError::DecodeError(base64::DecodeError::InvalidByte(
+                        index,
+                        _,
+                    )) => {
+                        let (index, c) = char_at(v, index);
+                        ByteArrayError::InvalidCharacter { c, index, encoding }
+                    }
+                    base64::DecodeSliceError::DecodeError(base64::DecodeError::InvalidPadding) => {
+                        ByteArrayError::InvalidPadding {
+                            from: unpadded_len,
+                            to: v.len().saturating_sub(1),
+                        }
+                    }
+                    base64::DecodeSliceError::DecodeError(
+                        base64::DecodeError::InvalidLastSymbol(index, _),
+                    ) => {
+                        let (index, c) = char_at(v, index);
+                        ByteArrayError::InvalidLastSymbol { c, index, encoding }
+                    }
+                    base64::DecodeSliceError::DecodeError(base64::DecodeError::InvalidLength(
+                        _,
+                    ))
+                    | base64::DecodeSliceError::OutputSliceTooSmall => invalid_length,
+                }),
+            Decoding::Hex => decode_hex(v, 0, data, encoding),
+            Decoding::HexPrefixed => {
+                if v.starts_with("0x") || v.starts_with("0X") {
+                    decode_hex(v, 2, data, encoding)
+                } else {
+                    Err(ByteArrayError::MissingPrefix)
+                }
+            }
+            Decoding::Base32 => crockford::decode_to_slice(v, data).map_err(|e| match e {
+                crockford::DecodeError::InvalidCharacter { c, index } => {
+                    ByteArrayError::InvalidCharacter { c, index, encoding }
+                }
+                crockford::DecodeError::InvalidLastSymbol { c, index } => {
+                    ByteArrayError::InvalidLastSymbol { c, index, encoding }
+                }
+                crockford::DecodeError::OutputSliceTooSmall => invalid_length,
+            }),
+            #[cfg(feature = "base58")]
+            Decoding::Base58 => bs58::decode(v)
+                .onto(&mut *data)
+                .map_err(|e| base58_error(v, data.len(), e)),
+        }
+    }
+}
+
+impl<B: FixedBytes> ByteArrayVisitor<B> {
+    fn invalid_padding() -> ByteArrayError {
+        ByteArrayError::InvalidPadding {
+            from: base64_unpadded_size(B::LEN),
+            to: base64_padded_size(B::LEN) - 1,
+        }
+    }
+
+    fn invalid_length(v: &str) -> ByteArrayError {
+        ByteArrayError::InvalidLength {
+            expected: B::LEN,
+            actual: v.len(),
+        }
+    }
+
+    fn decoding(&self, v: &str) -> Result<Option<Decoding>, ByteArrayError> {
+        match (self.encoding, B::LEN) {
+            (_, 0) if v.is_empty() => Ok(None),
+            (Encoding::Auto, 2 | 4) => {
+                if v.len() == base64_unpadded_size(B::LEN) {
+                    Ok(Some(Decoding::base64(v, base64_unpadded_size(B::LEN))))
+                } else if v.len() != B::LEN * 2 {
+                    Err(Self::invalid_length(v))
+                } else if base64_check_padding(v, B::LEN) {
+                    Ok(Some(Decoding::base64(v, base64_unpadded_size(B::LEN))))
+                } else {
+                    Ok(Some(Decoding::Hex))
+                }
+            }
+            (Encoding::Auto, _) => {
+                if v.len() == base64_padded_size(B::LEN) {
+                    if base64_check_padding(v, B::LEN) {
+                        Ok(Some(Decoding::base64(v, base64_unpadded_size(B::LEN))))
+                    } else {
+                        Err(Self::invalid_padding())
+                    }
+                } else if v.len() == hex_size(B::LEN) {
+                    // for a single byte this also is the length of unpadded base64, hex takes precedence
+                    Ok(Some(Decoding::Hex))
+                } else if v.len() == base64_unpadded_size(B::LEN) {
+                    Ok(Some(Decoding::base64(v, base64_unpadded_size(B::LEN))))
+                } else {
+                    Err(Self::invalid_length(v))
+                }
+            }
+            (Encoding::Hex, _) => {
+                if v.len() == hex_size(B::LEN) {
+                    Ok(Some(Decoding::Hex))
+                } else {
+                    Err(Self::invalid_length(v))
+                }
+            }
+            (Encoding::HexPrefixed, _) => {
+                if v.len() == hex_size(B::LEN) + 2 {
+                    Ok(Some(Decoding::HexPrefixed))
+                } else {
+                    Err(Self::invalid_length(v))
+                }
+            }
+            (Encoding::Base32, _) => {
+                if v.len() == crockford::encoded_size(B::LEN) {
+                    Ok(Some(Decoding::Base32))
+                } else {
+                    Err(Self::invalid_length(v))
+                }
+            }
+            // the length of base58 depends on the value, it is checked after decoding
+            #[cfg(feature = "base58")]
+            (Encoding::Base58, _) => Ok(Some(Decoding::Base58)),
+            (Encoding::Base64 | Encoding::Base64Url, _) => {
+                if v.len() == base64_padded_size(B::LEN) && !base64_check_padding(v, B::LEN) {
+                    Err(Self::invalid_padding())
+                } else if v.len() != base64_padded_size(B::LEN)
+                    && v.len() != base64_unpadded_size(B::LEN)
+                {
+                    Err(Self::invalid_length(v))
+                } else if self.encoding == Encoding::Base64Url {
+                    Ok(Some(Decoding::Base64(
+                        StringEncoding::Base64Url,
+                        base64_unpadded_size(B::LEN),
+                    )))
+                } else {
+                    Ok(Some(Decoding::Base64(
+                        StringEncoding::Base64,
+                        base64_unpadded_size(B::LEN),
+                    )))
+                }
+            }
+        }
+    }
+
+    /// Decodes a string without going through serde.
+    pub(crate) fn decode(&self, v: &str) -> Result<B, ByteArrayError> {
+        #[cfg(feature = "alloc")]
+        if self.lenient && v.contains(is_separator) {
+            let normalized: alloc::string::String =
+                v.chars().filter(|&c| !is_separator(c)).collect();
+            return self
+                .decode_strict(&normalized)
+                .map_err(|e| e.map_index(|index| original_index(v, index)));
+        }
+        self.decode_strict(v)
+    }
+
+    fn decode_strict(&self, v: &str) -> Result<B, ByteArrayError> {
+        let mut data = B::zeroed();
+        let len = match self.decoding(v)? {
+            None => 0,
+            Some(decoding) => decoding.decode(v, data.as_mut())?,
+        };
+        if len != B::LEN {
+            // only base58 may decode to a different number of bytes
+            return Err(ByteArrayError::InvalidLength {
+                expected: B::LEN,
+                actual: len,
+            });
+        }
+        Ok(data)
+    }
+}
+
+fn base64_expecting(formatter: &mut core::fmt::Formatter, n: usize) -> core::fmt::Result {
+    if base64_padded_size(n) == base64_unpadded_size(n) {
+        write!(formatter, "{}", base64_padded_size(n))
+    } else {
+        write!(
+            formatter,
+            "{} ({} without padding)",
+            base64_padded_size(n),
+            base64_unpadded_size(n)
+        )
+    }
+}
+
+impl<'de, B: FixedBytes> Visitor<'de> for ByteArrayVisitor<B> {
+    type Value = B;
+
+    fn expecting(&self, formatter: &mut core::fmt::Formatter) -> core::fmt::Result {
+        if B::LEN == 0 {
+            return write!(
+                formatter,
+                "bytes of length 0, an empty array, or an empty string"
+            );
+        }
+        write!(formatter, "bytes or an array of length {} or ", B::LEN)?;
+        match (self.encoding, B::LEN) {
+            (Encoding::Auto, 1) => write!(formatter, "a hex string of length {} or a base64 or base64url string of length {} (unpadded base64 is read as hex)", hex_size(B::LEN), base64_padded_size(B::LEN)),
+            (Encoding::Auto, 2 | 4) => write!(formatter, "a hex, base64 or base64url string of length {} or an unpadded base64 or base64url string of length {}", B::LEN * 2, base64_unpadded_size(B::LEN)),
+            (Encoding::Auto, _) => {
+                write!(formatter, "a hex string of length {} or a base64 or base64url string of length ", hex_size(B::LEN))?;
+                base64_expecting(formatter, B::LEN)
+            }
+            (Encoding::Hex, _) => write!(formatter, "a hex string of length {}", hex_size(B::LEN)),
+            (Encoding::HexPrefixed, _) => write!(formatter, "a 0x-prefixed hex string of length {}", hex_size(B::LEN) + 2),
+            (Encoding::Base32, _) => write!(formatter, "a Crockford base32 string of length {}", crockford::encoded_size(B::LEN)),
+            #[cfg(feature = "base58")]
+            (Encoding::Base58, _) => write!(formatter, "a base58 string"),
+            (Encoding::Base64, _) => {
+                write!(formatter, "a base64 string of length ")?;
+                base64_expecting(formatter, B::LEN)
+            }
+            (Encoding::Base64Url, _) => {
+                write!(formatter, "a base64url string of length ")?;
+                base64_expecting(formatter, B::LEN)
+            }
+        }?;
+        if self.is_lenient() {
+            write!(formatter, " (whitespace and `:` are ignored)")?;
+        }
+        Ok(())
+    }
+
+    fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
+    where
+        E: Error,
+    {
+        self.decode(v).map_err(|e| to_serde(e, &self))
+    }
+
+    fn visit_bytes<E>(self, v: &[u8]) -> Result<Self::Value, E>
+    where
+        E: Error,
+    {
+        if v.len() != B::LEN {
+            return Err(to_serde(
+                ByteArrayError::InvalidLength {
+                    expected: B::LEN,
+                    actual: v.len(),
+                },
+                &self,
+            ));
+        }
+        let mut data = B::zeroed();
+        data.as_mut().copy_from_slice(v);
+        Ok(data)
+    }
+
+    fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+    where
+        A: SeqAccess<'de>,
+    {
+        let invalid_length = |actual| ByteArrayError::InvalidLength {
+            expected: B::LEN,
+            actual,
+        };
+
+        if let Some(len) = seq.size_hint() {
+            if len != B::LEN {
+                return Err(to_serde(invalid_length(len), &self));
+            }
+        }
+
+        let mut data = B::zeroed();
+        for (i, data) in data.as_mut().iter_mut().enumerate() {
+            if let Some(item) = seq.next_element::<u8>()? {
+                *data = item;
+            } else {
+                return Err(to_serde(invalid_length(i), &self));
+            }
+        }
+
+        let mut too_many_elements = 0;
+
+        while seq.next_element::<u8>()?.is_some() {
+            too_many_elements += 1;
+        }
+
+        if too_many_elements > 0 {
+            return Err(to_serde(
+                ByteArrayError::TooManyElements {
+                    expected: B::LEN,
+                    actual: B::LEN + too_many_elements,
+                },
+                &self,
+            ));
+        }
+
+        Ok(data)
+    }
+}
+
+/// The most bytes a [`ByteVecVisitor`] reserves for a sequence before reading its elements.
+#[cfg(any(feature = "alloc", feature = "heapless"))]
+const MAX_PREALLOCATED: usize = 4096;
+
+/// The output of a [`ByteVecVisitor`], up to `CAPACITY` bytes.
+#[cfg(any(feature = "alloc", feature = "heapless"))]
+pub(crate) trait VarBytes: Sized {
+    const CAPACITY: usize;
+
+    /// `len` zeroed bytes, `len` is at most `CAPACITY`.
+    fn zeroed(len: usize) -> Self;
+
+    /// Room for `capacity` bytes, `capacity` is at most `CAPACITY`.
+    fn with_capacity(capacity: usize) -> Self;
+
+    /// A copy of `v`, the length of `v` is at most `CAPACITY`.
+    fn from_slice(v: &[u8]) -> Self;
+
+    fn as_mut_slice(&mut self) -> &mut [u8];
+
+    /// Shortens the bytes to `len` and releases unused memory.
+    fn finish(&mut self, len: usize);
+
+    /// Appends a byte, there is room for it.
+    fn push_byte(&mut self, byte: u8);
+
+    #[cfg(feature = "alloc")]
+    fn from_vec(v: Vec<u8>) -> Self {
+        Self::from_slice(&v)
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl VarBytes for Vec<u8> {
+    const CAPACITY: usize = usize::MAX;
+
+    fn zeroed(len: usize) -> Self {
+        vec![0; len]
+    }
+
+    fn with_capacity(capacity: usize) -> Self {
+        Vec::with_capacity(capacity)
+    }
+
+    fn from_slice(v: &[u8]) -> Self {
+        v.to_vec()
+    }
+
+    fn as_mut_slice(&mut self) -> &mut [u8] {
+        self
+    }
+
+    fn finish(&mut self, len: usize) {
+        self.truncate(len);
+        self.shrink_to_fit();
+    }
+
+    fn push_byte(&mut self, byte: u8) {
+        self.push(byte);
+    }
+
+    fn from_vec(v: Vec<u8>) -> Self {
+        v
+    }
+}
+
+#[cfg(feature = "heapless")]
+impl<const N: usize> VarBytes for heapless::Vec<u8, N> {
+    const CAPACITY: usize = N;
+
+    fn zeroed(len: usize) -> Self {
+        let mut bytes = heapless::Vec::new();
+        let _ = bytes.resize(len, 0);
+        bytes
+    }
+
+    fn with_capacity(_capacity: usize) -> Self {
+        heapless::Vec::new()
+    }
+
+    fn from_slice(v: &[u8]) -> Self {
+        heapless::Vec::from_slice(v).unwrap_or_default()
+    }
+
+    fn as_mut_slice(&mut self) -> &mut [u8] {
+        self
+    }
+
+    fn finish(&mut self, len: usize) {
+        self.truncate(len);
+    }
+
+    fn push_byte(&mut self, byte: u8) {
+        let _ = self.push(byte);
+    }
+}
+
+/// Like [`ByteArrayVisitor`], but for a variable number of bytes between `min` and `max` (inclusive).
+///
+/// Since the length of a string does not tell hex and base64 apart, [`Encoding::Auto`] only reads
+/// base64 in either alphabet, and hex needs [`Encoding::Hex`].
+#[cfg(any(feature = "alloc", feature = "heapless"))]
+pub(crate) struct ByteVecVisitor<B> {
+    encoding: Encoding,
+    min: usize,
+    max: usize,
+    output: PhantomData<B>,
+}
+
+#[cfg(any(feature = "alloc", feature = "heapless"))]
+impl<B: VarBytes> ByteVecVisitor<B> {
+    /// `max` is limited to the capacity of `B`.
+    pub(crate) fn new(encoding: Encoding, min: usize, max: usize) -> Self {
+        Self {
+            encoding,
+            min,
+            max: max.min(B::CAPACITY),
+            output: PhantomData,
+        }
+    }
+
+    fn decoding(&self, v: &str) -> Result<Option<Decoding>, ByteArrayError> {
+        if v.is_empty() {
+            return Ok(None);
+        }
+        match self.encoding {
+            Encoding::Hex => return Ok(Some(Decoding::Hex)),
+            Encoding::HexPrefixed => return Ok(Some(Decoding::HexPrefixed)),
+            Encoding::Base32 => return Ok(Some(Decoding::Base32)),
+            #[cfg(feature = "base58")]
+            Encoding::Base58 => return Ok(Some(Decoding::Base58)),
+            Encoding::Auto | Encoding::Base64 | Encoding::Base64Url => {}
+        }
+        let unpadded_len = v.trim_end_matches('=').len();
+        if unpadded_len != v.len() && (!v.len().is_multiple_of(4) || v.len() - unpadded_len > 2) {
+            return Err(ByteArrayError::InvalidPadding {
+                from: unpadded_len,
+                to: v.len() - 1,
+            });
+        }
+        Ok(Some(match self.encoding {
+            Encoding::Base64 => Decoding::Base64(StringEncoding::Base64, unpadded_len),
+            Encoding::Base64Url => Decoding::Base64(StringEncoding::Base64Url, unpadded_len),
+            _ => Decoding::base64(v, unpadded_len),
+        }))
+    }
+
+    fn check_length<E: Error>(&self, len: usize) -> Result<(), E> {
+        if len < self.min || len > self.max {
+            Err(self.length_error(len))
+        } else {
+            Ok(())
+        }
+    }
+
+    fn length_error<E: Error>(&self, len: usize) -> E {
+        let expected = if len < self.min { self.min } else { self.max };
+        to_serde(
+            ByteArrayError::InvalidLength {
+                expected,
+                actual: len,
+            },
+            self,
+        )
+    }
+}
+
+#[cfg(any(feature = "alloc", feature = "heapless"))]
+impl<'de, B: VarBytes> Visitor<'de> for ByteVecVisitor<B> {
+    type Value = B;
+
+    fn expecting(&self, formatter: &mut core::fmt::Formatter) -> core::fmt::Result {
+        match self.encoding {
+            Encoding::Auto => write!(formatter, "bytes, an array or a base64 or base64url string")?,
+            Encoding::Hex => write!(formatter, "bytes, an array or a hex string")?,
+            Encoding::Base64 => write!(formatter, "bytes, an array or a base64 string")?,
+            Encoding::Base64Url => write!(formatter, "bytes, an array or a base64url string")?,
+            Encoding::HexPrefixed => {
+                write!(formatter, "bytes, an array or a 0x-prefixed hex string")?
+            }
+            Encoding::Base32 => write!(formatter, "bytes, an array or a Crockford base32 string")?,
+            #[cfg(feature = "base58")]
+            Encoding::Base58 => write!(formatter, "bytes, an array or a base58 string")?,
+        }
+        match (self.min, self.max) {
+            (0, usize::MAX) => Ok(()),
+            (min, usize::MAX) => write!(formatter, " of at least {} bytes", min),
+            (0, max) => write!(formatter, " of at most {} bytes", max),
+            (min, max) if min == max => write!(formatter, " of exactly {} bytes", min),
+            (min, max) => write!(formatter, " of {} to {} bytes", min, max),
+        }
+    }
+
+    fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
+    where
+        E: Error,
+    {
+        let (mut data, len) = match self.decoding(v).map_err(|e| to_serde(e, &self))? {
+            None => (B::zeroed(0), 0),
+            Some(decoding) => {
+                let capacity = match decoding {
+                    Decoding::Hex => v.len() / 2,
+                    Decoding::HexPrefixed => v.len().saturating_sub(2) / 2,
+                    Decoding::Base64(_, unpadded_len) => unpadded_len * 3 / 4,
+                    Decoding::Base32 => v.len() * 5 / 8,
+                    // an upper bound, leading `1`s decode to zeros and take up one byte each
+                    #[cfg(feature = "base58")]
+                    Decoding::Base58 => v.len().min(self.max),
+                };
+                // fail before decoding into a buffer that is too large
+                if capacity > self.max {
+                    self.check_length(capacity)?;
+                }
+                let mut data = B::zeroed(capacity);
+                let len =
+                    decoding
+                        .decode(v, data.as_mut_slice())
+                        .map_err(|e| match (decoding, e) {
+                            // base58 that does not fit into `max` bytes
+                            #[cfg(feature = "base58")]
+                            (Decoding::Base58, ByteArrayError::InvalidLength { actual, .. }) => {
+                                self.length_error(actual)
+                            }
+                            (_, e) => to_serde(e, &self),
+                        })?;
+                (data, len)
+            }
+        };
+        self.check_length(len)?;
+        data.finish(len);
+        Ok(data)
+    }
+
+    fn visit_bytes<E>(self, v: &[u8]) -> Result<Self::Value, E>
+    where
+        E: Error,
+    {
+        self.check_length(v.len())?;
+        Ok(B::from_slice(v))
+    }
+
+    #[cfg(feature = "alloc")]
+    fn visit_byte_buf<E>(self, v: Vec<u8>) -> Result<Self::Value, E>
+    where
+        E: Error,
+    {
+        self.check_length(v.len())?;
+        Ok(B::from_vec(v))
+    }
+
+    fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+    where
+        A: SeqAccess<'de>,
+    {
+        if let Some(len) = seq.size_hint() {
+            self.check_length(len)?;
+        }
+
+        // the length is claimed by the input, do not trust it for large allocations
+        let capacity = seq.size_hint().unwrap_or(0).min(self.max);
+        let mut data = B::with_capacity(capacity.min(MAX_PREALLOCATED));
+        let mut len = 0;
+        while let Some(item) = seq.next_element::<u8>()? {
+            // keep counting, but do not store more than `max` elements
+            if len < self.max {
+                data.push_byte(item);
+            }
+            len += 1;
+        }
+
+        self.check_length(len)?;
+        Ok(data)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(feature = "alloc")]
+    use super::ByteVecVisitor;
+    use super::{ByteArrayVisitor, Encoding};
+    use crate::{ByteArrayError, StringEncoding};
+    use ::base64::display::Base64Display;
+    use ::base64::prelude::{
+        BASE64_STANDARD, BASE64_STANDARD_NO_PAD, BASE64_URL_SAFE, BASE64_URL_SAFE_NO_PAD,
+    };
+    use proptest::collection::vec;
+    use proptest::prelude::any;
+    use proptest::test_runner::TestRunner;
+    use serde::de::value::{BytesDeserializer, Error as ValueError};
+    use serde::de::Deserializer as _;
+
+    fn decode<const N: usize>(encoding: Encoding, v: &str) -> Result<[u8; N], ByteArrayError> {
+        ByteArrayVisitor::<[u8; N]>::new(encoding).decode(v)
+    }
+
+    fn round_trip<const N: usize>() {
+        let mut runner = TestRunner::default();
+        runner
+            .run(&vec(any::<u8>(), N), |bytes| {
+                let expected: [u8; N] = bytes.as_slice().try_into().unwrap();
+
+                let hex = crate::HexDisplay::new(&bytes).to_string();
+                assert_eq!(decode(Encoding::Auto, &hex), Ok(expected));
+                assert_eq!(decode(Encoding::Hex, &hex.to_uppercase()), Ok(expected));
+                let prefixed = format!("0x{}", hex);
+                assert_eq!(decode(Encoding::HexPrefixed, &prefixed), Ok(expected));
+
+                for (engine, encoding) in [
+                    (&BASE64_STANDARD, Encoding::Base64),
+                    (&BASE64_URL_SAFE, Encoding::Base64Url),
+                ] {
+                    let padded = Base64Display::new(&bytes, engine).to_string();
+                    assert_eq!(decode(Encoding::Auto, &padded), Ok(expected));
+                    assert_eq!(decode(encoding, &padded), Ok(expected));
+                }
+                for (engine, encoding) in [
+                    (&BASE64_STANDARD_NO_PAD, Encoding::Base64),
+                    (&BASE64_URL_SAFE_NO_PAD, Encoding::Base64Url),
+                ] {
+                    let unpadded = Base64Display::new(&bytes, engine).to_string();
+                    // for N = 1 unpadded base64 is read as hex
+                    if N != 1 {
+                        assert_eq!(decode(Encoding::Auto, &unpadded), Ok(expected));
+                    }
+                    assert_eq!(decode(encoding, &unpadded), Ok(expected));
+                }
+
+                let base32 = crate::crockford::Encoded(&bytes).to_string();
+                assert_eq!(decode(Encoding::Base32, &base32), Ok(expected));
+
+                let json = serde_json::to_string(&bytes).unwrap();
+                let value: [u8; N] = serde_json::Deserializer::from_str(&json)
+                    .deserialize_seq(ByteArrayVisitor::<[u8; N]>::new(Encoding::Auto))
+                    .unwrap();
+                assert_eq!(value, expected);
+
+                let value: [u8; N] = BytesDeserializer::<ValueError>::new(&bytes)
+                    .deserialize_bytes(ByteArrayVisitor::<[u8; N]>::new(Encoding::Auto))
+                    .unwrap();
+                assert_eq!(value, expected);
+
+                #[cfg(feature = "alloc")]
+                {
+                    let value = ByteVecVisitor::<Vec<u8>>::new(Encoding::Hex, N, N);
+                    let value = serde::de::Visitor::visit_str::<ValueError>(value, &hex).unwrap();
+                    assert_eq!(value, bytes);
+                }
+                Ok(())
+            })
+            .unwrap();
+    }
+
+    macro_rules! round_trips {
+        ($($n:literal)*) => {
+            $(round_trip::<$n>();)*
+        };
+    }
+
+    #[test]
+    fn round_trips() {
+        round_trips!(
+            0 1 2 3 4 5 6 7 8 9 10 11 12 13 14 15 16 17 18 19 20 21 22 23 24 25 26 27 28 29 30 31 32
+            33 34 35 36 37 38 39 40 41 42 43 44 45 46 47 48 49 50 51 52 53 54 55 56 57 58 59 60 61
+            62 63 64
+        );
+    }
+
+    #[test]
+    fn length_errors() {
+        let invalid_length =
+            |expected, actual| Some(ByteArrayError::InvalidLength { expected, actual });
+
+        // N = 2 and N = 4: hex and padded base64 share a length
+        assert_eq!(decode::<2>(Encoding::Auto, "3q0"), Ok([0xde, 0xad]));
+        assert_eq!(
+            decode::<2>(Encoding::Auto, "de").err(),
+            invalid_length(2, 2)
+        );
+        assert_eq!(
+            decode::<4>(Encoding::Auto, "3q2-7w"),
+            Ok([0xde, 0xad, 0xbe, 0xef])
+        );
+        assert_eq!(
+            decode::<4>(Encoding::Auto, "deadbeef00").err(),
+            invalid_length(4, 10)
+        );
+        // other N: padded base64, hex, unpadded base64
+        assert_eq!(
+            decode::<3>(Encoding::Auto, "deadbe00").err(),
+            invalid_length(3, 8)
+        );
+        assert_eq!(
+            decode::<5>(Encoding::Auto, "dead").err(),
+            invalid_length(5, 4)
+        );
+        assert_eq!(
+            decode::<0>(Encoding::Auto, "00").err(),
+            invalid_length(0, 2)
+        );
+
+        assert_eq!(
+            decode::<2>(Encoding::Hex, "dea").err(),
+            invalid_length(2, 3)
+        );
+        assert_eq!(
+            decode::<2>(Encoding::HexPrefixed, "dead").err(),
+            invalid_length(2, 4)
+        );
+        assert_eq!(
+            decode::<2>(Encoding::Base32, "VTP").err(),
+            invalid_length(2, 3)
+        );
+        assert_eq!(
+            decode::<2>(Encoding::Base64, "3q0==").err(),
+            invalid_length(2, 5)
+        );
+        assert_eq!(
+            decode::<2>(Encoding::Base64Url, "3q").err(),
+            invalid_length(2, 2)
+        );
+    }
+
+    #[test]
+    fn padding_errors() {
+        assert_eq!(
+            decode::<5>(Encoding::Auto, "3q2+7w=A"),
+            Err(ByteArrayError::InvalidPadding { from: 7, to: 7 })
+        );
+        assert_eq!(
+            decode::<4>(Encoding::Base64, "3q2+7w=A"),
+            Err(ByteArrayError::InvalidPadding { from: 6, to: 7 })
+        );
+        assert_eq!(
+            decode::<4>(Encoding::Base64Url, "3q2-7wA="),
+            Err(ByteArrayError::InvalidPadding { from: 6, to: 7 })
+        );
+    }
+
+    #[test]
+    fn character_errors() {
+        let invalid_character =
+            |c, index, encoding| Some(ByteArrayError::InvalidCharacter { c, index, encoding });
+
+        assert_eq!(
+            decode::<2>(Encoding::Hex, "deaz").err(),
+            invalid_character('z', 3, StringEncoding::Hex)
+        );
+        assert_eq!(
+            decode::<2>(Encoding::HexPrefixed, "0xdeaz").err(),
+            invalid_character('z', 5, StringEncoding::HexPrefixed)
+        );
+        assert_eq!(
+            decode::<2>(Encoding::HexPrefixed, "00dead"),
+            Err(ByteArrayError::MissingPrefix)
+        );
+        assert_eq!(
+            decode::<3>(Encoding::Auto, "3q2!").err(),
+            invalid_character('!', 3, StringEncoding::Base64)
+        );
+        assert_eq!(
+            decode::<3>(Encoding::Base64, "3q2_").err(),
+            invalid_character('_', 3, StringEncoding::Base64)
+        );
+        assert_eq!(
+            decode::<3>(Encoding::Base64Url, "3q2+").err(),
+            invalid_character('+', 3, StringEncoding::Base64Url)
+        );
+        assert_eq!(
+            decode::<2>(Encoding::Base64, "3q1"),
+            Err(ByteArrayError::InvalidLastSymbol {
+                c: '1',
+                index: 2,
+                encoding: StringEncoding::Base64
+            })
+        );
+        assert_eq!(
+            decode::<2>(Encoding::Base32, "VTPU").err(),
+            invalid_character('U', 3, StringEncoding::Base32)
+        );
+        assert_eq!(
+            decode::<1>(Encoding::Base32, "01"),
+            Err(ByteArrayError::InvalidLastSymbol {
+                c: '1',
+                index: 1,
+                encoding: StringEncoding::Base32
+            })
+        );
+
+        // non-ASCII characters are reported as a whole, at the index they start
+        assert_eq!(
+            decode::<2>(Encoding::Auto, "éé").err(),
+            invalid_character('é', 0, StringEncoding::Hex)
+        );
+        assert_eq!(
+            decode::<1>(Encoding::HexPrefixed, "0xé").err(),
+            invalid_character('é', 2, StringEncoding::HexPrefixed)
+        );
+        assert_eq!(
+            decode::<3>(Encoding::Base64, "3qé").err(),
+            invalid_character('é', 2, StringEncoding::Base64)
+        );
+        assert_eq!(
+            decode::<2>(Encoding::Base32, "VTé").err(),
+            invalid_character('é', 2, StringEncoding::Base32)
+        );
+    }
+
+    #[cfg(feature = "base58")]
+    #[test]
+    fn base58_errors() {
+        assert_eq!(
+            decode::<4>(Encoding::Base58, "6h0cQN"),
+            Err(ByteArrayError::InvalidCharacter {
+                c: '0',
+                index: 2,
+                encoding: StringEncoding::Base58
+            })
+        );
+        assert_eq!(
+            decode::<4>(Encoding::Base58, "6hécQN"),
+            Err(ByteArrayError::InvalidCharacter {
+                c: 'é',
+                index: 2,
+                encoding: StringEncoding::Base58
+            })
+        );
+        assert_eq!(
+            decode::<4>(Encoding::Base58, "111"),
+            Err(ByteArrayError::InvalidLength {
+                expected: 4,
+                actual: 3
+            })
+        );
+        // lengths are in bytes, not characters
+        assert_eq!(
+            decode::<4>(Encoding::Base58, "2Ho8Z"),
+            Err(ByteArrayError::InvalidLength {
+                expected: 4,
+                actual: 3
+            })
+        );
+        assert_eq!(
+            decode::<2>(Encoding::Base58, "6h8cQN"),
+            Err(ByteArrayError::InvalidLength {
+                expected: 2,
+                actual: 4
+            })
+        );
+        assert_eq!(
+            decode::<2>(Encoding::Base58, "1111111"),
+            Err(ByteArrayError::InvalidLength {
+                expected: 2,
+                actual: 7
+            })
+        );
+        assert_eq!(
+            decode::<2>(Encoding::Base58, "6h8cQN0"),
+            Err(ByteArrayError::InvalidCharacter {
+                c: '0',
+                index: 6,
+                encoding: StringEncoding::Base58
+            })
+        );
+
+        #[cfg(feature = "alloc")]
+        {
+            let visit_str = |min, max, v| {
+                serde::de::Visitor::visit_str::<ValueError>(
+                    ByteVecVisitor::<Vec<u8>>::new(Encoding::Base58, min, max),
+                    v,
+                )
+                .map_err(|e| e.to_string())
+            };
+            assert_eq!(visit_str(0, 4, "6h8cQN"), Ok(vec![0xde, 0xad, 0xbe, 0xef]));
+            assert_eq!(
+                visit_str(0, 3, "6h8cQN"),
+                Err(
+                    "invalid length 4, expected bytes, an array or a base58 string of at most 3 bytes"
+                        .to_owned()
+                )
+            );
+            assert_eq!(
+                visit_str(0, 2, "1111111"),
+                Err(
+                    "invalid length 7, expected bytes, an array or a base58 string of at most 2 bytes"
+                        .to_owned()
+                )
+            );
+            assert_eq!(
+                visit_str(8, 16, "6h8cQN"),
+                Err(
+                    "invalid length 4, expected bytes, an array or a base58 string of 8 to 16 bytes"
+                        .to_owned()
+                )
+            );
+        }
+    }
+
+    #[test]
+    fn sequence_errors() {
+        let visitor = || ByteArrayVisitor::<[u8; 2]>::new(Encoding::Auto);
+        let error = serde_json::Deserializer::from_str("[1]")
+            .deserialize_seq(visitor())
+            .unwrap_err();
+        assert!(error.to_string().starts_with("invalid length 1, expected"));
+        let error = serde_json::Deserializer::from_str("[1, 2, 3, 4]")
+            .deserialize_seq(visitor())
+            .unwrap_err();
+        assert!(error.to_string().starts_with("invalid length 4, expected"));
+        let error = serde_json::Deserializer::from_str(r#"[1, "a"]"#)
+            .deserialize_seq(visitor())
+            .unwrap_err();
+        assert!(error.to_string().starts_with("invalid type: string"));
+        let error = BytesDeserializer::<ValueError>::new(&[1, 2, 3])
+            .deserialize_bytes(visitor())
+            .unwrap_err();
+        assert!(error.to_string().starts_with("invalid length 3, expected"));
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn vec_errors() {
+        let visit_str = |encoding, min, max, v| {
+            serde::de::Visitor::visit_str::<ValueError>(
+                ByteVecVisitor::<Vec<u8>>::new(encoding, min, max),
+                v,
+            )
+            .map_err(|e| e.to_string())
+        };
+        assert_eq!(visit_str(Encoding::Hex, 0, 4, "dead"), Ok(vec![0xde, 0xad]));
+        // valid hex is not read as hex without pinning the encoding
+        assert_eq!(visit_str(Encoding::Auto, 0, 4, "AAAA"), Ok(vec![0, 0, 0]));
+        assert_eq!(visit_str(Encoding::Hex, 0, 4, "AAAA"), Ok(vec![0xaa, 0xaa]));
+        assert_eq!(
+            visit_str(Encoding::Auto, 0, usize::MAX, "3q2+7w="),
+            Err("invalid value: invalid padding from index 6 to 6, expected bytes, an array or a base64 or base64url string".to_owned())
+        );
+        assert_eq!(
+            visit_str(Encoding::Auto, 0, usize::MAX, "3q2+7w==="),
+            Err("invalid value: invalid padding from index 6 to 8, expected bytes, an array or a base64 or base64url string".to_owned())
+        );
+        assert_eq!(
+            visit_str(Encoding::Hex, 4, 4, "dead"),
+            Err(
+                "invalid length 2, expected bytes, an array or a hex string of exactly 4 bytes"
+                    .to_owned()
+            )
+        );
+        assert_eq!(
+            visit_str(Encoding::Hex, 0, 1, "dead"),
+            Err(
+                "invalid length 2, expected bytes, an array or a hex string of at most 1 bytes"
+                    .to_owned()
+            )
+        );
+        assert_eq!(
+            visit_str(Encoding::Hex, 0, usize::MAX, "3q2+7w=="),
+            Err("invalid value: invalid character `q` at index 1, expected bytes, an array or a hex string".to_owned())
+        );
+        assert_eq!(
+            visit_str(Encoding::Auto, 0, usize::MAX, "3q2é"),
+            Err("invalid value: invalid character `é` at index 3, expected bytes, an array or a base64 or base64url string".to_owned())
+        );
+    }
+}